@@ -1,21 +1,18 @@
-//! Allow your users to perform actions by pressing a button.
+//! Draw a [`Button`] and a [`Toggle`] with any `iced_graphics` backend.
 //!
-//! A [`Button`] has some local [`State`].
+//! The same implementation is used by the wgpu renderer and by the
+//! OpenGL (glow) renderer, which also runs on software rasterizers.
 //!
-//! [`Button`]: type.Button.html
-//! [`State`]: struct.State.html
+//! [`Button`]: ../struct.Button.html
+//! [`Toggle`]: ../toggle/struct.Toggle.html
 use iced_graphics::{Backend, Primitive, Renderer};
 use iced_graphics::{Defaults, Text};
 use iced_native::mouse;
 use iced_native::{Background, Color, Element, Layout, Point, Rectangle, Vector};
 
-pub use iced_native::button::State;
 pub use iced_style::button::{Style, StyleSheet};
 
-/// A widget that produces a message when clicked.
-///
-/// This is an alias of an `iced_native` button with an `iced_wgpu::Renderer`.
-pub type Button<'a, Message, Backend> = super::Button<'a, Message, Renderer<Backend>>;
+use super::toggle;
 
 impl<B> super::Renderer for Renderer<B>
 where
@@ -31,13 +28,16 @@ where
         bounds: Rectangle,
         cursor_position: Point,
         is_pressed: bool,
+        is_disabled: bool,
         style: &Box<dyn StyleSheet>,
         content: &Element<'_, Message, Self>,
         content_layout: Layout<'_>,
     ) -> Self::Output {
         let is_mouse_over = bounds.contains(cursor_position);
 
-        let styling = if is_mouse_over {
+        let styling = if is_disabled {
+            style.disabled()
+        } else if is_mouse_over {
             if is_pressed {
                 style.pressed()
             } else {
@@ -59,43 +59,73 @@ where
         );
 
         (
-            if styling.background.is_some() || styling.border_width > 0 {
-                let background = Primitive::Quad {
-                    bounds,
-                    background: styling
-                        .background
-                        .unwrap_or(Background::Color(Color::TRANSPARENT)),
-                    border_radius: styling.border_radius,
-                    border_width: styling.border_width,
-                    border_color: styling.border_color,
-                };
-
-                if styling.shadow_offset == Vector::default() {
-                    Primitive::Group {
-                        primitives: vec![background, content],
-                    }
-                } else {
-                    // TODO: Implement proper shadow support
-                    let shadow = Primitive::Quad {
-                        bounds: Rectangle {
-                            x: bounds.x + styling.shadow_offset.x,
-                            y: bounds.y + styling.shadow_offset.y,
-                            ..bounds
-                        },
-                        background: Background::Color([0.0, 0.0, 0.0, 0.5].into()),
-                        border_radius: styling.border_radius,
-                        border_width: 0,
-                        border_color: Color::TRANSPARENT,
-                    };
-
-                    Primitive::Group {
-                        primitives: vec![shadow, background, content],
-                    }
-                }
+            with_background(bounds, &styling, content),
+            if is_mouse_over && !is_disabled {
+                mouse::Interaction::Pointer
+            } else {
+                mouse::Interaction::default()
+            },
+        )
+    }
+}
+
+impl<B> toggle::Renderer for Renderer<B>
+where
+    B: Backend,
+{
+    const DEFAULT_PADDING: u16 = 5;
+
+    type Style = Box<dyn toggle::StyleSheet>;
+
+    fn draw<Message>(
+        &mut self,
+        _defaults: &Defaults,
+        bounds: Rectangle,
+        cursor_position: Point,
+        is_on: bool,
+        is_pressed: bool,
+        is_disabled: bool,
+        style: &Box<dyn toggle::StyleSheet>,
+        icon: Option<(&Element<'_, Message, Self>, Layout<'_>)>,
+        content: &Element<'_, Message, Self>,
+        content_layout: Layout<'_>,
+    ) -> Self::Output {
+        let is_mouse_over = bounds.contains(cursor_position);
+
+        let styling = if is_disabled {
+            style.disabled(is_on)
+        } else if is_mouse_over {
+            if is_pressed {
+                style.pressed(is_on)
             } else {
-                content
+                style.hovered(is_on)
+            }
+        } else {
+            style.active(is_on)
+        };
+
+        let defaults = Defaults {
+            text: Text {
+                color: styling.text_color,
             },
-            if is_mouse_over {
+        };
+
+        let (content, _) = content.draw(self, &defaults, content_layout, cursor_position);
+
+        let content = match icon {
+            Some((icon, icon_layout)) => {
+                let (icon, _) = icon.draw(self, &defaults, icon_layout, cursor_position);
+
+                Primitive::Group {
+                    primitives: vec![icon, content],
+                }
+            }
+            None => content,
+        };
+
+        (
+            with_background(bounds, &styling, content),
+            if is_mouse_over && !is_disabled {
                 mouse::Interaction::Pointer
             } else {
                 mouse::Interaction::default()
@@ -103,3 +133,44 @@ where
         )
     }
 }
+
+/// Puts the background, border and shadow described by `styling` behind
+/// the already drawn `content`.
+fn with_background(bounds: Rectangle, styling: &Style, content: Primitive) -> Primitive {
+    if styling.background.is_some() || styling.border_width > 0 {
+        let background = Primitive::Quad {
+            bounds,
+            background: styling
+                .background
+                .unwrap_or(Background::Color(Color::TRANSPARENT)),
+            border_radius: styling.border_radius,
+            border_width: styling.border_width,
+            border_color: styling.border_color,
+        };
+
+        if styling.shadow_offset == Vector::default() {
+            Primitive::Group {
+                primitives: vec![background, content],
+            }
+        } else {
+            // TODO: Implement proper shadow support
+            let shadow = Primitive::Quad {
+                bounds: Rectangle {
+                    x: bounds.x + styling.shadow_offset.x,
+                    y: bounds.y + styling.shadow_offset.y,
+                    ..bounds
+                },
+                background: Background::Color([0.0, 0.0, 0.0, 0.5].into()),
+                border_radius: styling.border_radius,
+                border_width: 0,
+                border_color: Color::TRANSPARENT,
+            };
+
+            Primitive::Group {
+                primitives: vec![shadow, background, content],
+            }
+        }
+    } else {
+        content
+    }
+}
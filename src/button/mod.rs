@@ -10,23 +10,36 @@ use iced_native::{
 use std::hash::Hash;
 
 mod graphics;
-pub use graphics::Button as WgpuButton;
+mod null;
+pub mod toggle;
+
+pub use graphics::{Style, StyleSheet};
+pub use toggle::Toggle;
+
 /// A generic widget that produces a message when pressed.
 ///
-/// ```
-/// # use iced_native::{button, Text};
+/// The widget is generic over its [`Renderer`], which is implemented for
+/// every `iced_graphics` backend (wgpu, glow, ...) and for the
+/// `iced_native` null renderer.
+///
+/// ```ignore
+/// # use iced_native::Text;
 /// #
 /// # type Button<'a, Message> =
-/// #     iced_native::Button<'a, Message, iced_native::renderer::Null>;
+/// #     button::Button<'a, Message, iced_native::renderer::Null>;
 /// #
+/// #[derive(Clone)]
 /// enum Message {
 ///     ButtonPressed,
 /// }
 ///
 /// let mut state = button::State::new();
-/// let button = Button::new(&mut state, Text::new("Press me!"))
-///     .on_press(Message::ButtonPressed);
+/// let button: Button<'_, Message> = Button::new(&mut state, Text::new("Press me!"), || {
+///     Message::ButtonPressed
+/// });
 /// ```
+///
+/// [`Renderer`]: trait.Renderer.html
 #[allow(missing_debug_implementations)]
 pub struct Button<'a, Message, Renderer: self::Renderer> {
     state: &'a mut State,
@@ -37,6 +50,7 @@ pub struct Button<'a, Message, Renderer: self::Renderer> {
     min_width: u32,
     min_height: u32,
     padding: u16,
+    is_disabled: bool,
     style: Renderer::Style,
 }
 
@@ -63,6 +77,7 @@ where
             min_width: 0,
             min_height: 0,
             padding: Renderer::DEFAULT_PADDING,
+            is_disabled: false,
             style: Renderer::Style::default(),
         }
     }
@@ -107,6 +122,17 @@ where
         self
     }
 
+    /// Disables the [`Button`].
+    ///
+    /// A disabled [`Button`] ignores every event and is drawn with the
+    /// disabled style.
+    ///
+    /// [`Button`]: struct.Button.html
+    pub fn disabled(mut self, is_disabled: bool) -> Self {
+        self.is_disabled = is_disabled;
+        self
+    }
+
     /// Sets the style of the [`Button`].
     ///
     /// [`Button`]: struct.Button.html
@@ -172,6 +198,11 @@ where
         _renderer: &Renderer,
         _clipboard: Option<&dyn Clipboard>,
     ) {
+        if self.is_disabled {
+            self.state.is_pressed = false;
+            return;
+        }
+
         match event {
             Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
                 let bounds = layout.bounds();
@@ -205,6 +236,7 @@ where
             layout.bounds(),
             cursor_position,
             self.state.is_pressed,
+            self.is_disabled,
             &self.style,
             &self.content,
             layout.children().next().unwrap(),
@@ -245,6 +277,7 @@ pub trait Renderer: iced_native::Renderer + Sized {
         bounds: Rectangle,
         cursor_position: Point,
         is_pressed: bool,
+        is_disabled: bool,
         style: &Self::Style,
         content: &Element<'_, Message, Self>,
         content_layout: Layout<'_>,
@@ -260,3 +293,98 @@ where
         Element::new(button)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use iced_native::renderer::Null;
+    use iced_native::{Size, Text};
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Message {
+        ButtonPressed,
+    }
+
+    fn button(state: &mut State) -> Button<'_, Message, Null> {
+        Button::new(state, Text::new("Press me!"), || Message::ButtonPressed)
+    }
+
+    fn layout(button: &Button<'_, Message, Null>) -> layout::Node {
+        let limits = layout::Limits::new(Size::ZERO, Size::new(200.0, 100.0));
+        Widget::layout(button, &Null, &limits)
+    }
+
+    /// Presses the left mouse button at `pressed` and releases it at
+    /// `released`, returning the messages.
+    fn click(
+        button: &mut Button<'_, Message, Null>,
+        pressed: Point,
+        released: Point,
+    ) -> Vec<Message> {
+        let node = layout(button);
+        let mut messages = Vec::new();
+
+        let clicks = [
+            (mouse::Event::ButtonPressed(mouse::Button::Left), pressed),
+            (mouse::Event::ButtonReleased(mouse::Button::Left), released),
+        ];
+        for (event, position) in clicks.iter().cloned() {
+            button.on_event(
+                Event::Mouse(event),
+                Layout::new(&node),
+                position,
+                &mut messages,
+                &Null,
+                None,
+            );
+        }
+        messages
+    }
+
+    #[test]
+    fn pads_the_content_up_to_the_minimum_size() {
+        let mut state = State::new();
+        let button = button(&mut state).padding(5).min_width(80);
+
+        let node = layout(&button);
+        let content = node.children()[0].bounds();
+
+        // The null renderer measures text as 0 by 20.
+        assert_eq!(node.size(), Size::new(80.0, 30.0));
+        assert_eq!((content.x, content.y), (5.0, 5.0));
+    }
+
+    #[test]
+    fn presses_when_released_over_the_button() {
+        let mut state = State::new();
+        let mut button = button(&mut state).min_width(80);
+        let inside = Point::new(10.0, 10.0);
+
+        assert_eq!(
+            click(&mut button, inside, inside),
+            vec![Message::ButtonPressed]
+        );
+    }
+
+    #[test]
+    fn ignores_a_press_dragged_out_or_started_outside() {
+        let mut state = State::new();
+        let mut button = button(&mut state).min_width(80);
+        let inside = Point::new(10.0, 10.0);
+        let outside = Point::new(150.0, 10.0);
+
+        assert!(click(&mut button, inside, outside).is_empty());
+        assert!(click(&mut button, outside, inside).is_empty());
+    }
+
+    #[test]
+    fn ignores_presses_while_disabled() {
+        let mut state = State::new();
+        let mut button = button(&mut state).min_width(80).disabled(true);
+        let inside = Point::new(10.0, 10.0);
+
+        assert!(click(&mut button, inside, inside).is_empty());
+        assert!(!button.state.is_pressed);
+    }
+}
@@ -0,0 +1,44 @@
+use iced_native::renderer::Null;
+use iced_native::{Element, Layout, Point, Rectangle};
+
+use super::toggle;
+
+impl super::Renderer for Null {
+    const DEFAULT_PADDING: u16 = 0;
+
+    type Style = ();
+
+    fn draw<Message>(
+        &mut self,
+        _defaults: &(),
+        _bounds: Rectangle,
+        _cursor_position: Point,
+        _is_pressed: bool,
+        _is_disabled: bool,
+        _style: &(),
+        _content: &Element<'_, Message, Self>,
+        _content_layout: Layout<'_>,
+    ) {
+    }
+}
+
+impl toggle::Renderer for Null {
+    const DEFAULT_PADDING: u16 = 0;
+
+    type Style = ();
+
+    fn draw<Message>(
+        &mut self,
+        _defaults: &(),
+        _bounds: Rectangle,
+        _cursor_position: Point,
+        _is_on: bool,
+        _is_pressed: bool,
+        _is_disabled: bool,
+        _style: &(),
+        _icon: Option<(&Element<'_, Message, Self>, Layout<'_>)>,
+        _content: &Element<'_, Message, Self>,
+        _content_layout: Layout<'_>,
+    ) {
+    }
+}
@@ -0,0 +1,513 @@
+//! Let your users switch something on and off.
+//!
+//! A [`Toggle`] shares its local [`State`] with the [`Button`] and carries
+//! the on/off value it represents.
+//!
+//! [`Toggle`]: struct.Toggle.html
+//! [`State`]: ../struct.State.html
+//! [`Button`]: ../struct.Button.html
+use iced_native::{
+    layout, mouse, Background, Clipboard, Color, Element, Event, Hasher, Layout, Length, Point,
+    Rectangle, Size, Vector, Widget,
+};
+use std::hash::Hash;
+
+pub use super::Style;
+use super::State;
+
+/// The horizontal space between the icon and the content of a [`Toggle`].
+///
+/// [`Toggle`]: struct.Toggle.html
+const ICON_SPACING: u16 = 8;
+
+/// A widget with an on/off state that produces a message when toggled.
+///
+/// The message receives the requested state, i.e. the opposite of the
+/// current one.
+#[allow(missing_debug_implementations)]
+pub struct Toggle<'a, Message, Renderer: self::Renderer> {
+    state: &'a mut State,
+    is_on: bool,
+    icon: Option<Element<'a, Message, Renderer>>,
+    content: Element<'a, Message, Renderer>,
+    on_toggle: Box<dyn Fn(bool) -> Message>,
+    width: Length,
+    height: Length,
+    padding: u16,
+    is_disabled: bool,
+    style: Renderer::Style,
+}
+
+impl<'a, Message, Renderer> Toggle<'a, Message, Renderer>
+where
+    Renderer: self::Renderer,
+{
+    /// Creates a new [`Toggle`] with some local [`State`], its current
+    /// value and the given content.
+    ///
+    /// [`Toggle`]: struct.Toggle.html
+    /// [`State`]: ../struct.State.html
+    pub fn new<E, F>(state: &'a mut State, is_on: bool, content: E, on_toggle: F) -> Self
+    where
+        E: Into<Element<'a, Message, Renderer>>,
+        F: 'static + Fn(bool) -> Message,
+    {
+        Toggle {
+            state,
+            is_on,
+            icon: None,
+            content: content.into(),
+            on_toggle: Box::new(on_toggle),
+            width: Length::Shrink,
+            height: Length::Shrink,
+            padding: Renderer::DEFAULT_PADDING,
+            is_disabled: false,
+            style: Renderer::Style::default(),
+        }
+    }
+
+    /// Sets an icon drawn before the content of the [`Toggle`].
+    ///
+    /// [`Toggle`]: struct.Toggle.html
+    pub fn icon<E>(mut self, icon: E) -> Self
+    where
+        E: Into<Element<'a, Message, Renderer>>,
+    {
+        self.icon = Some(icon.into());
+        self
+    }
+
+    /// Sets the width of the [`Toggle`].
+    ///
+    /// [`Toggle`]: struct.Toggle.html
+    pub fn width(mut self, width: Length) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Sets the height of the [`Toggle`].
+    ///
+    /// [`Toggle`]: struct.Toggle.html
+    pub fn height(mut self, height: Length) -> Self {
+        self.height = height;
+        self
+    }
+
+    /// Sets the padding of the [`Toggle`].
+    ///
+    /// [`Toggle`]: struct.Toggle.html
+    pub fn padding(mut self, padding: u16) -> Self {
+        self.padding = padding;
+        self
+    }
+
+    /// Disables the [`Toggle`].
+    ///
+    /// A disabled [`Toggle`] ignores every event and is drawn with the
+    /// disabled style of its current value.
+    ///
+    /// [`Toggle`]: struct.Toggle.html
+    pub fn disabled(mut self, is_disabled: bool) -> Self {
+        self.is_disabled = is_disabled;
+        self
+    }
+
+    /// Sets the style of the [`Toggle`].
+    ///
+    /// [`Toggle`]: struct.Toggle.html
+    pub fn style(mut self, style: impl Into<Renderer::Style>) -> Self {
+        self.style = style.into();
+        self
+    }
+
+    /// Returns whether the [`Toggle`] is on.
+    ///
+    /// [`Toggle`]: struct.Toggle.html
+    pub fn is_on(&self) -> bool {
+        self.is_on
+    }
+}
+
+impl<'a, Message, Renderer> Widget<Message, Renderer> for Toggle<'a, Message, Renderer>
+where
+    Renderer: self::Renderer,
+    Message: Clone,
+{
+    fn width(&self) -> Length {
+        self.width
+    }
+
+    fn height(&self) -> Length {
+        self.height
+    }
+
+    fn layout(&self, renderer: &Renderer, limits: &layout::Limits) -> layout::Node {
+        let padding = f32::from(self.padding);
+        let limits = limits.width(self.width).height(self.height).pad(padding);
+
+        let mut children = Vec::with_capacity(2);
+        let mut offset = 0.0;
+        let mut height: f32 = 0.0;
+
+        if let Some(icon) = &self.icon {
+            let mut icon = icon.layout(renderer, &limits.loose());
+            icon.move_to(Point::new(padding, padding));
+
+            offset = icon.size().width + f32::from(ICON_SPACING);
+            height = icon.size().height;
+            children.push(icon);
+        }
+
+        let mut content = self
+            .content
+            .layout(renderer, &limits.shrink(Size::new(offset, 0.0)));
+        content.move_to(Point::new(padding + offset, padding));
+
+        let size = limits
+            .resolve(Size::new(
+                offset + content.size().width,
+                height.max(content.size().height),
+            ))
+            .pad(padding);
+
+        children.push(content);
+
+        layout::Node::with_children(size, children)
+    }
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        messages: &mut Vec<Message>,
+        _renderer: &Renderer,
+        _clipboard: Option<&dyn Clipboard>,
+    ) {
+        if self.is_disabled {
+            self.state.is_pressed = false;
+            return;
+        }
+
+        match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                let bounds = layout.bounds();
+
+                self.state.is_pressed = bounds.contains(cursor_position);
+            }
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                let bounds = layout.bounds();
+
+                let is_clicked = self.state.is_pressed && bounds.contains(cursor_position);
+
+                self.state.is_pressed = false;
+
+                if is_clicked {
+                    messages.push((self.on_toggle)(!self.is_on));
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut Renderer,
+        defaults: &Renderer::Defaults,
+        layout: Layout<'_>,
+        cursor_position: Point,
+    ) -> Renderer::Output {
+        let mut children = layout.children();
+        let icon = self
+            .icon
+            .as_ref()
+            .map(|icon| (icon, children.next().unwrap()));
+
+        renderer.draw(
+            defaults,
+            layout.bounds(),
+            cursor_position,
+            self.is_on,
+            self.state.is_pressed,
+            self.is_disabled,
+            &self.style,
+            icon,
+            &self.content,
+            children.next().unwrap(),
+        )
+    }
+
+    fn hash_layout(&self, state: &mut Hasher) {
+        struct Marker;
+        std::any::TypeId::of::<Marker>().hash(state);
+
+        self.width.hash(state);
+        self.height.hash(state);
+        self.padding.hash(state);
+
+        if let Some(icon) = &self.icon {
+            icon.hash_layout(state);
+        }
+        self.content.hash_layout(state);
+    }
+}
+
+/// The renderer of a [`Toggle`].
+///
+/// Your [renderer] will need to implement this trait before being
+/// able to use a [`Toggle`] in your user interface.
+///
+/// [`Toggle`]: struct.Toggle.html
+/// [renderer]: ../../renderer/index.html
+pub trait Renderer: iced_native::Renderer + Sized {
+    /// The default padding of a [`Toggle`].
+    ///
+    /// [`Toggle`]: struct.Toggle.html
+    const DEFAULT_PADDING: u16;
+
+    /// The style supported by this renderer.
+    type Style: Default;
+
+    /// Draws a [`Toggle`].
+    ///
+    /// [`Toggle`]: struct.Toggle.html
+    fn draw<Message>(
+        &mut self,
+        defaults: &Self::Defaults,
+        bounds: Rectangle,
+        cursor_position: Point,
+        is_on: bool,
+        is_pressed: bool,
+        is_disabled: bool,
+        style: &Self::Style,
+        icon: Option<(&Element<'_, Message, Self>, Layout<'_>)>,
+        content: &Element<'_, Message, Self>,
+        content_layout: Layout<'_>,
+    ) -> Self::Output;
+}
+
+/// A set of rules that dictate the style of a [`Toggle`].
+///
+/// Every state has its own entry, so "on" can look different from "off"
+/// even while hovered or disabled.
+///
+/// [`Toggle`]: struct.Toggle.html
+pub trait StyleSheet {
+    /// The style of a [`Toggle`] that is on.
+    ///
+    /// [`Toggle`]: struct.Toggle.html
+    fn on(&self) -> Style;
+
+    /// The style of a [`Toggle`] that is off.
+    ///
+    /// [`Toggle`]: struct.Toggle.html
+    fn off(&self) -> Style;
+
+    /// The style of a hovered [`Toggle`].
+    ///
+    /// [`Toggle`]: struct.Toggle.html
+    fn hovered(&self, is_on: bool) -> Style {
+        let active = self.active(is_on);
+
+        Style {
+            shadow_offset: active.shadow_offset + Vector::new(0.0, 1.0),
+            ..active
+        }
+    }
+
+    /// The style of a pressed [`Toggle`].
+    ///
+    /// [`Toggle`]: struct.Toggle.html
+    fn pressed(&self, is_on: bool) -> Style {
+        Style {
+            shadow_offset: Vector::default(),
+            ..self.active(is_on)
+        }
+    }
+
+    /// The style of a disabled [`Toggle`].
+    ///
+    /// [`Toggle`]: struct.Toggle.html
+    fn disabled(&self, is_on: bool) -> Style {
+        let active = self.active(is_on);
+
+        Style {
+            shadow_offset: Vector::default(),
+            background: active.background.map(|background| match background {
+                Background::Color(color) => Background::Color(Color {
+                    a: color.a * 0.5,
+                    ..color
+                }),
+            }),
+            text_color: Color {
+                a: active.text_color.a * 0.5,
+                ..active.text_color
+            },
+            ..active
+        }
+    }
+
+    /// The style matching the given value, not hovered nor pressed.
+    fn active(&self, is_on: bool) -> Style {
+        if is_on {
+            self.on()
+        } else {
+            self.off()
+        }
+    }
+}
+
+struct Standard;
+
+impl StyleSheet for Standard {
+    fn on(&self) -> Style {
+        Style {
+            shadow_offset: Vector::new(0.0, 0.0),
+            background: Some(Background::Color([0.87, 0.33, 0.33].into())),
+            border_radius: 2,
+            border_width: 1,
+            border_color: [0.6, 0.2, 0.2].into(),
+            text_color: Color::WHITE,
+        }
+    }
+
+    fn off(&self) -> Style {
+        Style {
+            shadow_offset: Vector::new(0.0, 0.0),
+            background: Some(Background::Color([0.87, 0.87, 0.87].into())),
+            border_radius: 2,
+            border_width: 1,
+            border_color: [0.7, 0.7, 0.7].into(),
+            text_color: Color::BLACK,
+        }
+    }
+}
+
+impl std::default::Default for Box<dyn StyleSheet> {
+    fn default() -> Self {
+        Box::new(Standard)
+    }
+}
+
+impl<T> From<T> for Box<dyn StyleSheet>
+where
+    T: 'static + StyleSheet,
+{
+    fn from(style: T) -> Self {
+        Box::new(style)
+    }
+}
+
+impl<'a, Message, Renderer> From<Toggle<'a, Message, Renderer>> for Element<'a, Message, Renderer>
+where
+    Renderer: 'a + self::Renderer,
+    Message: 'a + Clone,
+{
+    fn from(toggle: Toggle<'a, Message, Renderer>) -> Element<'a, Message, Renderer> {
+        Element::new(toggle)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use iced_native::renderer::Null;
+    use iced_native::Text;
+
+    #[derive(Debug, Clone, PartialEq)]
+    enum Message {
+        Toggled(bool),
+    }
+
+    fn toggle(state: &mut State, is_on: bool) -> Toggle<'_, Message, Null> {
+        Toggle::new(state, is_on, Text::new("Muted"), Message::Toggled).width(Length::Units(80))
+    }
+
+    fn layout(toggle: &Toggle<'_, Message, Null>) -> layout::Node {
+        let limits = layout::Limits::new(Size::ZERO, Size::new(200.0, 100.0));
+        Widget::layout(toggle, &Null, &limits)
+    }
+
+    /// Presses the left mouse button at `pressed` and releases it at
+    /// `released`, returning the messages.
+    fn click(
+        toggle: &mut Toggle<'_, Message, Null>,
+        pressed: Point,
+        released: Point,
+    ) -> Vec<Message> {
+        let node = layout(toggle);
+        let mut messages = Vec::new();
+
+        let clicks = [
+            (mouse::Event::ButtonPressed(mouse::Button::Left), pressed),
+            (mouse::Event::ButtonReleased(mouse::Button::Left), released),
+        ];
+        for (event, position) in clicks.iter().cloned() {
+            toggle.on_event(
+                Event::Mouse(event),
+                Layout::new(&node),
+                position,
+                &mut messages,
+                &Null,
+                None,
+            );
+        }
+        messages
+    }
+
+    #[test]
+    fn lays_the_icon_out_before_the_content() {
+        let mut state = State::new();
+        let toggle = toggle(&mut state, false).icon(Text::new("x")).padding(5);
+
+        let node = layout(&toggle);
+        let icon = node.children()[0].bounds();
+        let content = node.children()[1].bounds();
+
+        // The null renderer measures text as 0 by 20.
+        assert_eq!(node.size(), Size::new(80.0, 30.0));
+        assert_eq!((icon.x, icon.y), (5.0, 5.0));
+        assert_eq!((content.x, content.y), (5.0 + f32::from(ICON_SPACING), 5.0));
+    }
+
+    #[test]
+    fn requests_the_opposite_value_when_clicked() {
+        let inside = Point::new(10.0, 10.0);
+
+        let mut state = State::new();
+        let mut off = toggle(&mut state, false);
+        assert_eq!(
+            click(&mut off, inside, inside),
+            vec![Message::Toggled(true)]
+        );
+
+        let mut state = State::new();
+        let mut on = toggle(&mut state, true);
+        assert_eq!(
+            click(&mut on, inside, inside),
+            vec![Message::Toggled(false)]
+        );
+    }
+
+    #[test]
+    fn ignores_a_press_dragged_out_or_started_outside() {
+        let mut state = State::new();
+        let mut toggle = toggle(&mut state, false);
+        let inside = Point::new(10.0, 10.0);
+        let outside = Point::new(150.0, 10.0);
+
+        assert!(click(&mut toggle, inside, outside).is_empty());
+        assert!(click(&mut toggle, outside, inside).is_empty());
+    }
+
+    #[test]
+    fn ignores_presses_while_disabled() {
+        let mut state = State::new();
+        let mut toggle = toggle(&mut state, false).disabled(true);
+        let inside = Point::new(10.0, 10.0);
+
+        assert!(click(&mut toggle, inside, inside).is_empty());
+        assert!(!toggle.state.is_pressed);
+    }
+}
@@ -0,0 +1,185 @@
+//! Command line of the application.
+use std::env;
+use std::process::{Child, Command};
+use std::time::Duration;
+
+use crate::fade::{self, FadeTarget};
+use crate::instance::Request;
+use crate::logging::{self, LogOptions};
+use crate::osd::Device;
+use crate::scene;
+
+pub const USAGE: &str = "\
+Usage: volume-controller-ui [OPTIONS] [COMMAND]
+
+Options:
+    --log-level LEVEL
+              Log from LEVEL on: off, error, warn, info, debug or trace
+              (also read from VOLUME_CONTROLLER_LOG)
+    --log-file PATH
+              Append the log to PATH instead of standard error (also read
+              from VOLUME_CONTROLLER_LOG_FILE)
+
+Commands:
+    window    Open the mixer window, or show the hidden one (default)
+    show      Same as window
+    toggle    Open or show the mixer window, or hide the shown one
+    focus APP Open the mixer window and highlight the streams of APP
+    popup     Open the compact mixer, without decorations
+    scene list|save NAME|apply NAME|diff NAME
+              List, save the current setup as, apply or compare scenes
+    fade [sink|source|APP] --to VOLUME [--over DURATION] [--curve CURVE]
+              Fade the default sink, the default source or the streams of
+              APP to VOLUME (e.g. 20%) over DURATION (e.g. 3s or 500ms,
+              1s by default), with a linear, ease-in-out or exponential
+              curve
+    tray      Run the system tray icon
+    ptt press|release
+              Press or release the push-to-talk trigger of the mixer window
+    osd       Show an on-screen display when the default devices change
+    osd-show sink|source
+              Show the on-screen display of a device
+    help      Show this message";
+
+/// What the process runs as.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Mode {
+    /// The mixer window, sent the request when it already runs.
+    Window(Request),
+    Popup,
+    /// A request only meaningful to a running mixer window.
+    Forward(Request),
+    Scene(scene::Command),
+    Fade(fade::Command),
+    Tray,
+    Osd,
+    OsdShow(Device),
+    Help,
+}
+
+/// Parses the command line, taking out the logging options wherever they
+/// appear.
+pub fn parse<I>(args: I) -> Result<(Mode, LogOptions), String>
+where
+    I: IntoIterator<Item = String>,
+{
+    let mut options = LogOptions::default();
+    let mut rest = Vec::new();
+    let mut args = args.into_iter();
+
+    while let Some(arg) = args.next() {
+        match arg.as_str() {
+            "--log-level" => {
+                let value = args.next().ok_or("Missing the value of --log-level")?;
+                options.level = Some(
+                    logging::parse_level(&value)
+                        .ok_or_else(|| format!("Invalid log level \"{}\"", value))?,
+                );
+            }
+            "--log-file" => {
+                options.file = Some(args.next().ok_or("Missing the value of --log-file")?.into());
+            }
+            _ => rest.push(arg),
+        }
+    }
+
+    parse_mode(rest).map(|mode| (mode, options))
+}
+
+fn parse_mode(args: Vec<String>) -> Result<Mode, String> {
+    let mut args = args.into_iter();
+
+    let mode = match args.next().as_deref() {
+        None | Some("window") | Some("show") => Mode::Window(Request::Show),
+        Some("toggle") => Mode::Window(Request::Toggle),
+        Some("focus") => match args.next() {
+            Some(application) => Mode::Window(Request::Focus(application)),
+            None => return Err("Missing the application of focus".into()),
+        },
+        Some("popup") => Mode::Popup,
+        Some("ptt") => match args.next().as_deref() {
+            Some("press") => Mode::Forward(Request::Talk(true)),
+            Some("release") => Mode::Forward(Request::Talk(false)),
+            Some(arg) => return Err(format!("Unknown trigger action \"{}\"", arg)),
+            None => return Err("Missing the action of ptt".into()),
+        },
+        Some("scene") => Mode::Scene(match args.next().as_deref() {
+            None | Some("list") => scene::Command::List,
+            Some(command @ "save") | Some(command @ "apply") | Some(command @ "diff") => {
+                let name = args.next().ok_or("Missing the name of the scene")?;
+                match command {
+                    "save" => scene::Command::Save(name),
+                    "apply" => scene::Command::Apply(name),
+                    _ => scene::Command::Diff(name),
+                }
+            }
+            Some(command) => return Err(format!("Unknown scene command \"{}\"", command)),
+        }),
+        Some("fade") => Mode::Fade(parse_fade(&mut args)?),
+        Some("tray") => Mode::Tray,
+        Some("osd") => Mode::Osd,
+        Some("osd-show") => match args.next() {
+            Some(arg) => match Device::from_arg(&arg) {
+                Some(device) => Mode::OsdShow(device),
+                None => return Err(format!("Unknown device \"{}\"", arg)),
+            },
+            None => return Err("Missing the device of osd-show".into()),
+        },
+        Some("help") | Some("-h") | Some("--help") => Mode::Help,
+        Some(command) => return Err(format!("Unknown command \"{}\"", command)),
+    };
+
+    match args.next() {
+        Some(arg) => Err(format!("Unexpected argument \"{}\"", arg)),
+        None => Ok(mode),
+    }
+}
+
+fn parse_fade<I>(args: &mut I) -> Result<fade::Command, String>
+where
+    I: Iterator<Item = String>,
+{
+    let mut target = FadeTarget::Sink;
+    let mut to = None;
+    let mut over = Duration::from_secs(1);
+    let mut curve = None;
+
+    while let Some(arg) = args.next() {
+        let mut value = || {
+            args.next()
+                .ok_or_else(|| format!("Missing the value of {}", arg))
+        };
+
+        match arg.as_str() {
+            "--to" => to = Some(fade::parse_volume(&value()?)?),
+            "--over" => over = fade::parse_duration(&value()?)?,
+            "--curve" => curve = Some(value()?.parse()?),
+            "sink" => target = FadeTarget::Sink,
+            "source" => target = FadeTarget::Source,
+            option if option.starts_with("--") => {
+                return Err(format!("Unknown option \"{}\"", option))
+            }
+            _ => target = FadeTarget::Application(arg),
+        }
+    }
+
+    Ok(fade::Command {
+        target,
+        to: to.ok_or("Missing the volume of fade")?,
+        over,
+        curve,
+    })
+}
+
+/// Starts another instance of this executable with the given arguments.
+pub fn spawn_self(args: &[&str]) -> Option<Child> {
+    let spawned = env::current_exe().and_then(|exe| Command::new(exe).args(args).spawn());
+
+    match spawned {
+        Ok(child) => Some(child),
+        Err(e) => {
+            log::error!("Unable to start \"{}\": {}", args.join(" "), e);
+            None
+        }
+    }
+}
@@ -0,0 +1,32 @@
+//! Copy to the clipboard through `wl-copy` or `xclip`.
+use std::env;
+use std::io::Write;
+use std::process::{Command, Stdio};
+
+/// Copies `text`, with `wl-copy` on Wayland and `xclip` elsewhere.
+pub fn copy(text: &str) -> Result<(), String> {
+    let (program, args): (&str, &[&str]) = if env::var_os("WAYLAND_DISPLAY").is_some() {
+        ("wl-copy", &[])
+    } else {
+        ("xclip", &["-selection", "clipboard"])
+    };
+
+    let mut child = Command::new(program)
+        .args(args)
+        .stdin(Stdio::piped())
+        .spawn()
+        .map_err(|e| format!("Unable to run {}: {}", program, e))?;
+
+    // Dropping the pipe closes it, so that the program reads to the end.
+    let written = child
+        .stdin
+        .take()
+        .map_or(Ok(()), |mut stdin| stdin.write_all(text.as_bytes()));
+    let status = child.wait();
+
+    match (written, status) {
+        (Ok(()), Ok(status)) if status.success() => Ok(()),
+        (Err(e), _) | (_, Err(e)) => Err(format!("Unable to copy with {}: {}", program, e)),
+        (_, Ok(status)) => Err(format!("{} failed with {}", program, status)),
+    }
+}
@@ -0,0 +1,95 @@
+//! User configuration, read from `$XDG_CONFIG_HOME/volume-controller/config.toml`.
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::ducking::DuckingConfig;
+use crate::fade::FadeConfig;
+use crate::filter::StreamsConfig;
+use crate::layout::Layout;
+use crate::limit::LimitConfig;
+use crate::osd::OsdConfig;
+use crate::ptt::PttConfig;
+use crate::sleep::SleepConfig;
+use crate::theme::ThemeConfig;
+
+const CONFIG_DIRECTORY: &str = "volume-controller";
+const CONFIG_FILE: &str = "config.toml";
+const RUNTIME_DIRECTORY: &str = "volume-controller";
+
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Config {
+    pub theme: ThemeConfig,
+    /// The layout the mixer starts with.
+    pub layout: Layout,
+    pub osd: OsdConfig,
+    pub ptt: PttConfig,
+    pub ducking: DuckingConfig,
+    pub fade: FadeConfig,
+    pub sleep: SleepConfig,
+    pub streams: StreamsConfig,
+    pub limits: LimitConfig,
+}
+
+impl Config {
+    /// Loads the configuration file, falling back to the defaults when it
+    /// is missing or invalid.
+    pub fn load() -> Self {
+        let path = match directory() {
+            Some(directory) => directory.join(CONFIG_FILE),
+            None => return Self::default(),
+        };
+
+        match fs::read_to_string(&path) {
+            Ok(content) => toml::from_str(&content).unwrap_or_else(|e| {
+                log::warn!("Invalid configuration {}: {}", path.display(), e);
+                Self::default()
+            }),
+            Err(e) if e.kind() == ErrorKind::NotFound => Self::default(),
+            Err(e) => {
+                log::warn!("Unable to read {}: {}", path.display(), e);
+                Self::default()
+            }
+        }
+    }
+}
+
+/// Replaces a section of the configuration file, keeping the others.
+pub fn save_section<T: Serialize>(name: &str, section: &T) -> Result<(), String> {
+    let path = directory()
+        .ok_or("No configuration directory")?
+        .join(CONFIG_FILE);
+
+    let mut table = match fs::read_to_string(&path) {
+        // An invalid file is left alone rather than overwritten.
+        Ok(content) => toml::from_str::<toml::value::Table>(&content)
+            .map_err(|e| format!("Invalid configuration {}: {}", path.display(), e))?,
+        Err(e) if e.kind() == ErrorKind::NotFound => toml::value::Table::new(),
+        Err(e) => return Err(format!("Unable to read {}: {}", path.display(), e)),
+    };
+    let section = toml::Value::try_from(section)
+        .map_err(|e| format!("Unable to save the {} section: {}", name, e))?;
+    table.insert(name.to_owned(), section);
+
+    // Written as a value, which puts the plain values before the tables.
+    let content = toml::to_string(&toml::Value::Table(table))
+        .map_err(|e| format!("Unable to save the {} section: {}", name, e))?;
+    path.parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(&path, content))
+        .map_err(|e| format!("Unable to write {}: {}", path.display(), e))
+}
+
+/// The directory holding the configuration and the files kept by the app.
+pub fn directory() -> Option<PathBuf> {
+    dirs::config_dir().map(|directory| directory.join(CONFIG_DIRECTORY))
+}
+
+/// The directory holding the files shared by the running processes of the
+/// app, in `$XDG_RUNTIME_DIR`.
+pub fn runtime_directory() -> Option<PathBuf> {
+    dirs::runtime_dir().map(|directory| directory.join(RUNTIME_DIRECTORY))
+}
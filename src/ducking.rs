@@ -0,0 +1,300 @@
+//! Automatic ducking of the other streams during calls.
+//!
+//! A call is any stream playing or recording with the `phone` media role,
+//! or belonging to one of the configured applications. While one is
+//! running, every other sink input is lowered by the configured
+//! attenuation; the volumes fade down and back up over the ramp duration.
+//!
+//! A stream whose volume is changed during the call, by the user or by
+//! another program, is left alone from then on: its new volume is kept
+//! once the call ends instead of the one it had before.
+use std::collections::HashMap;
+use std::time::Duration;
+
+use serde::Deserialize;
+
+use crate::updater::{SinkInputData, SourceOutputData};
+
+const PHONE_ROLE: &str = "phone";
+
+/// The `[ducking]` section of the configuration file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct DuckingConfig {
+    /// Off unless asked for, as it changes volumes on its own.
+    pub enabled: bool,
+    /// Applications starting a call, besides the streams with the `phone`
+    /// media role.
+    pub applications: Vec<String>,
+    /// How much the other streams are lowered, in percent of their volume.
+    pub attenuation: u32,
+    /// How long lowering and restoring the volumes take, in milliseconds.
+    pub ramp: u64,
+}
+
+impl Default for DuckingConfig {
+    fn default() -> Self {
+        Self {
+            enabled: false,
+            applications: Vec::new(),
+            attenuation: 60,
+            ramp: 500,
+        }
+    }
+}
+
+/// A stream lowered by the engine.
+#[derive(Debug)]
+struct Ducked {
+    /// The volume to restore.
+    original: u32,
+    /// The volume the engine lowered it to.
+    applied: u32,
+    /// Set once somebody else changed the volume.
+    is_overridden: bool,
+}
+
+/// A volume change wanted by the engine, to be faded over the ramp.
+#[derive(Debug, Clone, Copy)]
+pub struct Duck {
+    pub id: u32,
+    pub from: u32,
+    pub to: u32,
+}
+
+#[derive(Debug)]
+pub struct Ducking {
+    config: DuckingConfig,
+    applications: Vec<String>,
+    is_active: bool,
+    ducked: HashMap<u32, Ducked>,
+}
+
+impl Ducking {
+    pub fn new(config: &DuckingConfig) -> Self {
+        Self {
+            applications: config
+                .applications
+                .iter()
+                .map(|application| application.to_lowercase())
+                .collect(),
+            config: config.clone(),
+            is_active: false,
+            ducked: HashMap::new(),
+        }
+    }
+
+    pub fn is_enabled(&self) -> bool {
+        self.config.enabled
+    }
+
+    /// Returns `true` while the other streams are lowered.
+    pub fn is_active(&self) -> bool {
+        self.is_active
+    }
+
+    /// How long lowering and restoring a volume takes.
+    pub fn ramp(&self) -> Duration {
+        Duration::from_millis(self.config.ramp)
+    }
+
+    /// Returns the volume changes to fade.
+    ///
+    /// `is_busy` tells which sink inputs have changes in flight or are
+    /// fading; they are left as they are until they settle.
+    pub fn update<F>(
+        &mut self,
+        sink_inputs: &[SinkInputData],
+        source_outputs: &[SourceOutputData],
+        is_busy: F,
+    ) -> Vec<Duck>
+    where
+        F: Fn(u32) -> bool,
+    {
+        self.is_active = sink_inputs
+            .iter()
+            .any(|input| self.is_call(&input.name, input.role.as_deref()))
+            || source_outputs
+                .iter()
+                .any(|output| self.is_call(&output.name, output.role.as_deref()));
+
+        let factor = 1.0 - self.config.attenuation.min(100) as f32 / 100.0;
+        let mut ducks = Vec::new();
+
+        self.ducked
+            .retain(|id, _| sink_inputs.iter().any(|input| input.id == *id));
+
+        for input in sink_inputs {
+            if is_busy(input.id) {
+                continue;
+            }
+
+            let is_call = self.is_call(&input.name, input.role.as_deref());
+            if is_call || !self.is_active {
+                if let Some(ducked) = self.ducked.remove(&input.id) {
+                    if !ducked.is_overridden && input.volume == ducked.applied {
+                        ducks.push(Duck {
+                            id: input.id,
+                            from: input.volume,
+                            to: ducked.original,
+                        });
+                    }
+                }
+                continue;
+            }
+
+            match self.ducked.get_mut(&input.id) {
+                Some(ducked) => {
+                    if input.volume != ducked.applied {
+                        ducked.is_overridden = true;
+                    }
+                }
+                None => {
+                    let volume = (input.volume as f32 * factor).round() as u32;
+                    self.ducked.insert(
+                        input.id,
+                        Ducked {
+                            original: input.volume,
+                            applied: volume,
+                            is_overridden: false,
+                        },
+                    );
+                    ducks.push(Duck {
+                        id: input.id,
+                        from: input.volume,
+                        to: volume,
+                    });
+                }
+            }
+        }
+
+        ducks
+    }
+
+    fn is_call(&self, name: &str, role: Option<&str>) -> bool {
+        role == Some(PHONE_ROLE) || self.applications.contains(&name.to_lowercase())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CALL: u32 = 1;
+    const MUSIC: u32 = 2;
+
+    fn ducking() -> Ducking {
+        Ducking::new(&DuckingConfig {
+            enabled: true,
+            applications: vec![String::from("Zoom")],
+            attenuation: 50,
+            ..DuckingConfig::default()
+        })
+    }
+
+    fn stream(id: u32, name: &str, role: Option<&str>, volume: u32) -> SinkInputData {
+        SinkInputData {
+            id,
+            name: name.to_owned(),
+            role: role.map(String::from),
+            volume,
+            ..SinkInputData::default()
+        }
+    }
+
+    fn update(ducking: &mut Ducking, sink_inputs: &[SinkInputData]) -> Vec<(u32, u32, u32)> {
+        ducking
+            .update(sink_inputs, &[], |_| false)
+            .into_iter()
+            .map(|duck| (duck.id, duck.from, duck.to))
+            .collect()
+    }
+
+    #[test]
+    fn lowers_the_other_streams_when_a_call_starts() {
+        let mut ducking = ducking();
+        let music = stream(MUSIC, "mpv", None, 60000);
+
+        assert!(update(&mut ducking, std::slice::from_ref(&music)).is_empty());
+        assert!(!ducking.is_active());
+
+        let call = stream(CALL, "ZOOM", None, 65536);
+        assert_eq!(
+            update(&mut ducking, &[call, music]),
+            vec![(MUSIC, 60000, 30000)]
+        );
+        assert!(ducking.is_active());
+    }
+
+    #[test]
+    fn starts_a_call_from_a_recording_stream_of_the_phone_role() {
+        let mut ducking = ducking();
+        let recording = SourceOutputData {
+            id: CALL,
+            name: String::from("Softphone"),
+            role: Some(String::from(PHONE_ROLE)),
+        };
+
+        let ducks = ducking.update(&[stream(MUSIC, "mpv", None, 60000)], &[recording], |_| {
+            false
+        });
+        assert_eq!(ducks.len(), 1);
+        assert!(ducking.is_active());
+    }
+
+    #[test]
+    fn restores_the_volumes_when_the_call_ends() {
+        let mut ducking = ducking();
+        let call = stream(CALL, "Softphone", Some(PHONE_ROLE), 65536);
+        update(&mut ducking, &[call, stream(MUSIC, "mpv", None, 60000)]);
+
+        assert_eq!(
+            update(&mut ducking, &[stream(MUSIC, "mpv", None, 30000)]),
+            vec![(MUSIC, 30000, 60000)]
+        );
+        assert!(!ducking.is_active());
+    }
+
+    #[test]
+    fn keeps_a_volume_changed_during_the_call() {
+        let mut ducking = ducking();
+        let call = stream(CALL, "Zoom", None, 65536);
+        update(
+            &mut ducking,
+            &[call.clone(), stream(MUSIC, "mpv", None, 60000)],
+        );
+
+        let changed = stream(MUSIC, "mpv", None, 40000);
+        assert!(update(&mut ducking, &[call, changed.clone()]).is_empty());
+        assert!(update(&mut ducking, &[changed]).is_empty());
+    }
+
+    #[test]
+    fn lowers_a_stream_arriving_during_the_call() {
+        let mut ducking = ducking();
+        let call = stream(CALL, "Zoom", None, 65536);
+        update(
+            &mut ducking,
+            &[call.clone(), stream(MUSIC, "mpv", None, 60000)],
+        );
+
+        let music = stream(MUSIC, "mpv", None, 30000);
+        let video = stream(3, "Firefox", None, 20000);
+        assert_eq!(
+            update(&mut ducking, &[call, music, video]),
+            vec![(3, 20000, 10000)]
+        );
+    }
+
+    #[test]
+    fn waits_for_the_busy_streams_to_settle() {
+        let mut ducking = ducking();
+        let streams = [
+            stream(CALL, "Zoom", None, 65536),
+            stream(MUSIC, "mpv", None, 60000),
+        ];
+
+        assert!(ducking.update(&streams, &[], |id| id == MUSIC).is_empty());
+        assert_eq!(update(&mut ducking, &streams), vec![(MUSIC, 60000, 30000)]);
+    }
+}
@@ -0,0 +1,449 @@
+//! Volume fades of streams and devices.
+//!
+//! A [`Fader`] ramps volumes to their targets over a duration, following a
+//! [`Curve`]. It only computes the volumes: its owner calls [`step`]
+//! regularly and sends them, and cancels the fade of a target as soon as
+//! the user changes it.
+//!
+//! [`Fader`]: struct.Fader.html
+//! [`Curve`]: enum.Curve.html
+//! [`step`]: struct.Fader.html#method.step
+use std::cell::RefCell;
+use std::rc::Rc;
+use std::str::FromStr;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+use crate::config::Config;
+use crate::updater::{
+    fetch_sink_inputs, update_fetch_sink, update_fetch_source, update_sink_input_mute_by_id,
+    update_sink_input_volume_by_id, update_sink_mute, update_sink_volume, update_source_mute,
+    update_source_volume, MainData, PulseHandler, Target,
+};
+
+/// How often the command line sends the volumes of a fade.
+const STEP_INTERVAL: Duration = Duration::from_millis(20);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum Curve {
+    Linear,
+    /// Slow at both ends.
+    EaseInOut,
+    /// Even steps of loudness rather than of volume.
+    Exponential,
+}
+
+impl Default for Curve {
+    fn default() -> Self {
+        Curve::EaseInOut
+    }
+}
+
+impl Curve {
+    /// Maps the elapsed part of a fade to the part of the change made.
+    fn apply(self, progress: f32) -> f32 {
+        match self {
+            Curve::Linear => progress,
+            Curve::EaseInOut => progress * progress * (3.0 - 2.0 * progress),
+            Curve::Exponential => (2f32.powf(10.0 * progress) - 1.0) / 1023.0,
+        }
+    }
+
+    /// The volume `progress` of the way from `from` to `to`.
+    ///
+    /// A fall follows the curve of a rise backwards, so that the loudness
+    /// of an exponential fade-out drops as evenly as it grows in a fade-in,
+    /// instead of holding near `from` for most of the fade.
+    fn volume(self, from: u32, to: u32, progress: f32) -> u32 {
+        let part = if to < from {
+            1.0 - self.apply(1.0 - progress)
+        } else {
+            self.apply(progress)
+        };
+        (from as f32 + (to as f32 - from as f32) * part).round() as u32
+    }
+}
+
+impl FromStr for Curve {
+    type Err = String;
+
+    fn from_str(name: &str) -> Result<Self, String> {
+        match name {
+            "linear" => Ok(Curve::Linear),
+            "ease-in-out" => Ok(Curve::EaseInOut),
+            "exponential" => Ok(Curve::Exponential),
+            _ => Err(format!("Unknown curve \"{}\"", name)),
+        }
+    }
+}
+
+/// The `[fade]` section of the configuration file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct FadeConfig {
+    pub curve: Curve,
+    /// The duration of the fade-out buttons, in milliseconds.
+    pub fade_out: u64,
+    /// The duration of the volume changes of scenes, in milliseconds.
+    pub scene: u64,
+}
+
+impl Default for FadeConfig {
+    fn default() -> Self {
+        Self {
+            curve: Curve::default(),
+            fade_out: 2000,
+            scene: 1000,
+        }
+    }
+}
+
+/// A volume to send for a fade.
+#[derive(Debug, Clone, Copy)]
+pub struct Step {
+    pub target: Target,
+    pub volume: u32,
+    /// The volume sent before, for rolling back.
+    pub previous: u32,
+    /// Set on the last step of a fade-out.
+    pub mute: Option<bool>,
+}
+
+#[derive(Debug)]
+struct Fade {
+    target: Target,
+    from: u32,
+    to: u32,
+    current: u32,
+    duration: Duration,
+    curve: Curve,
+    started_at: Instant,
+    /// Mute at the end and restore the volume, for fade-outs.
+    then_mute: bool,
+}
+
+#[derive(Debug, Default)]
+pub struct Fader {
+    fades: Vec<Fade>,
+}
+
+impl Fader {
+    /// Fades `target` from `from` to `to`, replacing its running fade.
+    pub fn start(&mut self, target: Target, from: u32, to: u32, duration: Duration, curve: Curve) {
+        self.push(target, from, to, duration, curve, false);
+    }
+
+    /// Fades `target` out, then mutes it and restores its volume.
+    pub fn fade_out(&mut self, target: Target, from: u32, duration: Duration, curve: Curve) {
+        self.push(target, from, 0, duration, curve, true);
+    }
+
+    pub fn cancel(&mut self, target: Target) {
+        self.fades.retain(|fade| fade.target != target);
+    }
+
+    pub fn is_fading(&self, target: Target) -> bool {
+        self.fades.iter().any(|fade| fade.target == target)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.fades.is_empty()
+    }
+
+    /// Moves the fades forward, returning the volumes to send.
+    pub fn step(&mut self) -> Vec<Step> {
+        let mut steps = Vec::new();
+        let mut index = 0;
+
+        while index < self.fades.len() {
+            let fade = &mut self.fades[index];
+            let progress = if fade.duration == Duration::from_secs(0) {
+                1.0
+            } else {
+                (fade.started_at.elapsed().as_secs_f32() / fade.duration.as_secs_f32()).min(1.0)
+            };
+            let volume = fade.curve.volume(fade.from, fade.to, progress);
+            let is_done = progress >= 1.0;
+
+            if is_done && fade.then_mute {
+                steps.push(Step {
+                    target: fade.target,
+                    volume: fade.from,
+                    previous: fade.current,
+                    mute: Some(true),
+                });
+            } else if volume != fade.current {
+                steps.push(Step {
+                    target: fade.target,
+                    volume,
+                    previous: fade.current,
+                    mute: None,
+                });
+            }
+            fade.current = volume;
+
+            if is_done {
+                self.fades.remove(index);
+            } else {
+                index += 1;
+            }
+        }
+
+        steps
+    }
+
+    fn push(
+        &mut self,
+        target: Target,
+        from: u32,
+        to: u32,
+        duration: Duration,
+        curve: Curve,
+        then_mute: bool,
+    ) {
+        self.cancel(target);
+        self.fades.push(Fade {
+            target,
+            from,
+            to,
+            current: from,
+            duration,
+            curve,
+            started_at: Instant::now(),
+            then_mute,
+        });
+    }
+}
+
+/// Sends a step through `handler`, for the fades run outside the window.
+///
+/// The mute goes first, so that restoring the volume after a fade-out is
+/// not heard. Only a fade-out mutes, so the target was unmuted before.
+pub fn send(handler: &mut PulseHandler, step: Step) {
+    if let Some(mute) = step.mute {
+        match step.target {
+            Target::SinkInput(id) => update_sink_input_mute_by_id(handler, id, mute, !mute),
+            Target::Sink => update_sink_mute(handler, mute, !mute),
+            Target::Source => update_source_mute(handler, mute, !mute),
+        }
+    }
+
+    match step.target {
+        Target::SinkInput(id) => {
+            update_sink_input_volume_by_id(handler, id, step.volume, step.previous)
+        }
+        Target::Sink => update_sink_volume(handler, step.volume, step.previous),
+        Target::Source => update_source_volume(handler, step.volume, step.previous),
+    }
+}
+
+/// Runs the fades of `fader` to their end.
+pub fn finish(handler: &mut PulseHandler, fader: &mut Fader) {
+    while !fader.is_empty() {
+        for step in fader.step() {
+            send(handler, step);
+        }
+        handler.poll();
+        thread::sleep(STEP_INTERVAL);
+    }
+
+    for rejected in handler.settle() {
+        log::warn!("The server rejected the change of {:?}", rejected.target);
+    }
+}
+
+/// What the `fade` command changes.
+#[derive(Debug, Clone, PartialEq)]
+pub enum FadeTarget {
+    Sink,
+    Source,
+    /// Every stream of an application.
+    Application(String),
+}
+
+/// A `fade` command of the command line.
+#[derive(Debug, Clone, PartialEq)]
+pub struct Command {
+    pub target: FadeTarget,
+    pub to: u32,
+    pub over: Duration,
+    /// The curve of the configuration when not given.
+    pub curve: Option<Curve>,
+}
+
+/// Runs a `fade` command until the fade ends.
+pub fn run(command: Command) -> Result<(), String> {
+    let mut handler = PulseHandler::new().ok_or("Unable to connect to the server")?;
+    let mut fader = Fader::default();
+    let curve = command.curve.unwrap_or(Config::load().fade.curve);
+
+    let targets: Vec<(Target, u32)> = match &command.target {
+        FadeTarget::Sink | FadeTarget::Source => {
+            let data = Rc::new(RefCell::new(MainData::default()));
+            let target = if command.target == FadeTarget::Sink {
+                update_fetch_sink(&mut handler, data.clone());
+                Target::Sink
+            } else {
+                update_fetch_source(&mut handler, data.clone());
+                Target::Source
+            };
+            let volume = data.borrow().volume;
+            vec![(target, volume)]
+        }
+        FadeTarget::Application(name) => fetch_sink_inputs(&mut handler)
+            .into_iter()
+            .filter(|input| &input.name == name)
+            .map(|input| (Target::SinkInput(input.id), input.volume))
+            .collect(),
+    };
+    if targets.is_empty() {
+        return Err("No stream to fade".into());
+    }
+
+    for (target, volume) in targets {
+        fader.start(target, volume, command.to, command.over, curve);
+    }
+    finish(&mut handler, &mut fader);
+
+    Ok(())
+}
+
+/// Parses a volume given as a percentage, e.g. `20%`, or as a raw value.
+pub fn parse_volume(value: &str) -> Result<u32, String> {
+    let invalid = || format!("Invalid volume \"{}\"", value);
+
+    match value.strip_suffix('%') {
+        Some(percent) => percent
+            .parse::<f32>()
+            .map(|percent| (percent.max(0.0) * 65536.0 / 100.0).round() as u32)
+            .map_err(|_| invalid()),
+        None => value.parse().map_err(|_| invalid()),
+    }
+}
+
+/// Parses a duration such as `3s`, `1.5s` or `500ms`.
+pub fn parse_duration(value: &str) -> Result<Duration, String> {
+    let invalid = || format!("Invalid duration \"{}\"", value);
+
+    let (number, unit) = match value.find(|c: char| c.is_ascii_alphabetic()) {
+        Some(index) => value.split_at(index),
+        None => (value, "s"),
+    };
+    let number: f64 = number.parse().map_err(|_| invalid())?;
+    let seconds = match unit {
+        "ms" => number / 1000.0,
+        "s" => number,
+        "m" | "min" => number * 60.0,
+        _ => return Err(invalid()),
+    };
+
+    if seconds < 0.0 || !seconds.is_finite() {
+        return Err(invalid());
+    }
+    Ok(Duration::from_secs_f64(seconds))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const CURVES: [Curve; 3] = [Curve::Linear, Curve::EaseInOut, Curve::Exponential];
+
+    #[test]
+    fn parses_percentages_and_raw_volumes() {
+        assert_eq!(parse_volume("100%"), Ok(65536));
+        assert_eq!(parse_volume("50%"), Ok(32768));
+        assert_eq!(parse_volume("0%"), Ok(0));
+        assert_eq!(parse_volume("12.5%"), Ok(8192));
+        assert_eq!(parse_volume("-20%"), Ok(0));
+        assert_eq!(parse_volume("1000"), Ok(1000));
+    }
+
+    #[test]
+    fn rejects_invalid_volumes() {
+        for value in &["", "%", "loud", "20 %", "-1", "1.5"] {
+            assert!(parse_volume(value).is_err(), "{} was accepted", value);
+        }
+    }
+
+    #[test]
+    fn parses_durations_with_and_without_unit() {
+        assert_eq!(parse_duration("3"), Ok(Duration::from_secs(3)));
+        assert_eq!(parse_duration("1.5s"), Ok(Duration::from_millis(1500)));
+        assert_eq!(parse_duration("500ms"), Ok(Duration::from_millis(500)));
+        assert_eq!(parse_duration("2m"), Ok(Duration::from_secs(120)));
+        assert_eq!(parse_duration("1min"), Ok(Duration::from_secs(60)));
+        assert_eq!(parse_duration("0s"), Ok(Duration::from_secs(0)));
+    }
+
+    #[test]
+    fn rejects_invalid_durations() {
+        for value in &["", "s", "-1s", "3h", "inf", "NaN", "1.5.2s"] {
+            assert!(parse_duration(value).is_err(), "{} was accepted", value);
+        }
+    }
+
+    #[test]
+    fn curves_start_at_zero_and_end_at_one() {
+        for &curve in &CURVES {
+            assert_eq!(curve.apply(0.0), 0.0, "{:?}", curve);
+            assert!((curve.apply(1.0) - 1.0).abs() < 1e-6, "{:?}", curve);
+        }
+    }
+
+    #[test]
+    fn curves_only_move_forward() {
+        for &curve in &CURVES {
+            let steps: Vec<f32> = (0..=100)
+                .map(|step| curve.apply(step as f32 / 100.0))
+                .collect();
+            assert!(
+                steps.windows(2).all(|pair| pair[0] <= pair[1]),
+                "{:?} goes back",
+                curve
+            );
+        }
+    }
+
+    #[test]
+    fn curves_have_their_shape() {
+        assert_eq!(Curve::Linear.apply(0.25), 0.25);
+        // Symmetric around the middle, slow at both ends.
+        assert_eq!(Curve::EaseInOut.apply(0.5), 0.5);
+        assert!(Curve::EaseInOut.apply(0.1) < 0.1);
+        assert!(Curve::EaseInOut.apply(0.9) > 0.9);
+        // Most of the change comes last.
+        assert!(Curve::Exponential.apply(0.5) < 0.05);
+    }
+
+    #[test]
+    fn fades_out_as_they_fade_in_backwards() {
+        const FULL: u32 = 65536;
+
+        for &curve in &CURVES {
+            assert_eq!(curve.volume(FULL, 0, 0.0), FULL, "{:?}", curve);
+            assert_eq!(curve.volume(FULL, 0, 1.0), 0, "{:?}", curve);
+            assert!(curve.volume(FULL, 0, 0.5) <= FULL / 2, "{:?}", curve);
+            for step in 0..=10 {
+                let progress = step as f32 / 10.0;
+                let rise = curve.volume(0, FULL, 1.0 - progress);
+                let fall = curve.volume(FULL, 0, progress);
+                assert!((rise as i64 - fall as i64).abs() <= 1, "{:?}", curve);
+            }
+        }
+        // Well below the start halfway through, not near it.
+        assert!(Curve::Exponential.volume(FULL, 0, 0.5) < FULL / 20);
+        assert!(Curve::Exponential.volume(FULL, 0, 0.1) < FULL * 3 / 4);
+    }
+
+    #[test]
+    fn parses_curve_names() {
+        assert_eq!("linear".parse(), Ok(Curve::Linear));
+        assert_eq!("ease-in-out".parse(), Ok(Curve::EaseInOut));
+        assert_eq!("exponential".parse(), Ok(Curve::Exponential));
+        assert!("Linear".parse::<Curve>().is_err());
+    }
+}
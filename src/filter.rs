@@ -0,0 +1,125 @@
+//! Which application streams the mixer shows.
+//!
+//! The streams of an application are hidden by its name, listed in the
+//! `[streams]` section of the configuration file, and the event sounds and
+//! the streams of the mixer itself can be hidden as a whole. A search then
+//! narrows the rows down by application name or media title.
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+use crate::updater::{SinkInputData, CLIENT_NAME};
+
+const SECTION: &str = "streams";
+const EVENT_ROLE: &str = "event";
+
+/// The `[streams]` section of the configuration file.
+#[derive(Debug, Clone, Default, Serialize, Deserialize)]
+#[serde(default)]
+pub struct StreamsConfig {
+    /// Hide the streams whose role is `event`, such as notification sounds.
+    pub hide_events: bool,
+    /// Hide the streams of the mixer itself.
+    pub hide_own: bool,
+    /// Group the streams of an application into one row.
+    pub group: bool,
+    /// The applications whose streams are hidden.
+    pub hidden: Vec<String>,
+}
+
+impl StreamsConfig {
+    pub fn is_hidden(&self, input: &SinkInputData) -> bool {
+        self.hidden.contains(&input.name)
+            || (self.hide_events && input.role.as_deref() == Some(EVENT_ROLE))
+            || (self.hide_own && input.name == CLIENT_NAME)
+    }
+
+    /// Hides the streams of an application, or shows them again.
+    pub fn set_hidden(&mut self, name: &str, is_hidden: bool) {
+        self.hidden.retain(|hidden| hidden != name);
+        if is_hidden {
+            self.hidden.push(name.to_owned());
+        }
+    }
+
+    /// Writes the section into the configuration file.
+    pub fn save(&self) -> Result<(), String> {
+        config::save_section(SECTION, self)
+    }
+}
+
+/// Whether the stream matches the search, ignoring the case. Every stream
+/// matches an empty search.
+pub fn matches(search: &str, input: &SinkInputData) -> bool {
+    let search = search.trim().to_lowercase();
+
+    search.is_empty()
+        || std::iter::once(&input.name)
+            .chain(input.title.iter())
+            .any(|text| text.to_lowercase().contains(&search))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream(name: &str, title: Option<&str>, role: Option<&str>) -> SinkInputData {
+        SinkInputData {
+            name: name.to_owned(),
+            title: title.map(String::from),
+            role: role.map(String::from),
+            ..SinkInputData::default()
+        }
+    }
+
+    #[test]
+    fn matches_the_name_or_the_title_ignoring_the_case() {
+        let tab = stream("Firefox", Some("Lo-fi Radio - YouTube"), None);
+
+        assert!(matches("fire", &tab));
+        assert!(matches("FIREFOX", &tab));
+        assert!(matches("youtube", &tab));
+        assert!(matches("  radio ", &tab));
+        assert!(!matches("spotify", &tab));
+    }
+
+    #[test]
+    fn matches_everything_with_an_empty_search() {
+        let untitled = stream("mpv", None, None);
+
+        assert!(matches("", &untitled));
+        assert!(matches("   ", &untitled));
+        assert!(!matches("title", &untitled));
+    }
+
+    #[test]
+    fn hides_by_name_role_and_own_streams() {
+        let mut config = StreamsConfig::default();
+        let music = stream("Spotify", None, Some("music"));
+        let event = stream("Notifications", None, Some("event"));
+        let own = stream(CLIENT_NAME, None, None);
+
+        assert!(![&music, &event, &own]
+            .iter()
+            .any(|input| config.is_hidden(input)));
+
+        config.hide_events = true;
+        config.hide_own = true;
+        assert!(config.is_hidden(&event));
+        assert!(config.is_hidden(&own));
+        assert!(!config.is_hidden(&music));
+
+        config.set_hidden("Spotify", true);
+        assert!(config.is_hidden(&music));
+        config.set_hidden("Spotify", false);
+        assert!(!config.is_hidden(&music));
+    }
+
+    #[test]
+    fn lists_a_hidden_application_once() {
+        let mut config = StreamsConfig::default();
+        config.set_hidden("Spotify", true);
+        config.set_hidden("Spotify", true);
+
+        assert_eq!(config.hidden, vec!["Spotify"]);
+    }
+}
@@ -0,0 +1,139 @@
+//! Streams grouped by application.
+//!
+//! An application playing several streams, such as a browser with many
+//! tabs, gets one row whose volume is the loudest of its streams. Moving it
+//! scales every stream by the same ratio, from the volumes they had when the
+//! drag started, so that their balance survives a drag down to silence.
+use std::time::{Duration, Instant};
+
+use crate::updater::SinkInputData;
+
+/// The longest pause between two slider moves of a single drag.
+const DRAG_INTERVAL: Duration = Duration::from_millis(800);
+
+/// The streams of an application.
+#[derive(Debug, Clone)]
+pub struct Group {
+    pub name: String,
+    /// The positions of the streams in the stream list.
+    pub members: Vec<usize>,
+}
+
+/// Groups the streams by application name, in the order the applications
+/// first appear.
+pub fn by_application<'a, I>(inputs: I) -> Vec<Group>
+where
+    I: IntoIterator<Item = (usize, &'a SinkInputData)>,
+{
+    let mut groups: Vec<Group> = Vec::new();
+
+    for (position, input) in inputs {
+        match groups.iter_mut().find(|group| group.name == input.name) {
+            Some(group) => group.members.push(position),
+            None => groups.push(Group {
+                name: input.name.clone(),
+                members: vec![position],
+            }),
+        }
+    }
+    groups
+}
+
+/// A drag of the slider of a group.
+#[derive(Debug)]
+pub struct Scaling {
+    name: String,
+    /// The id and volume of each stream when the drag started.
+    start: Vec<(u32, u32)>,
+    peak: u32,
+    changed_at: Instant,
+}
+
+impl Scaling {
+    pub fn new(name: &str, start: Vec<(u32, u32)>) -> Self {
+        Self {
+            name: name.to_owned(),
+            peak: start.iter().map(|&(_, volume)| volume).max().unwrap_or(0),
+            start,
+            changed_at: Instant::now(),
+        }
+    }
+
+    /// Whether a move of the slider of `name` continues this drag.
+    pub fn continues(&self, name: &str) -> bool {
+        self.name == name && self.changed_at.elapsed() < DRAG_INTERVAL
+    }
+
+    /// The id and new volume of each stream, for the group at `volume`.
+    pub fn scale(&mut self, volume: u32) -> Vec<(u32, u32)> {
+        self.changed_at = Instant::now();
+
+        let peak = u64::from(self.peak);
+        self.start
+            .iter()
+            .map(|&(id, start)| {
+                // All silent: the ratios are lost, so they move together.
+                let scaled = match peak {
+                    0 => volume,
+                    _ => (u64::from(start) * u64::from(volume) / peak) as u32,
+                };
+                (id, scaled)
+            })
+            .collect()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn stream(name: &str) -> SinkInputData {
+        SinkInputData {
+            name: name.to_owned(),
+            ..SinkInputData::default()
+        }
+    }
+
+    #[test]
+    fn groups_by_name_in_the_order_of_appearance() {
+        let inputs = [stream("Firefox"), stream("mpv"), stream("Firefox")];
+        let groups = by_application(inputs.iter().enumerate());
+
+        let groups: Vec<_> = groups
+            .iter()
+            .map(|group| (group.name.as_str(), group.members.clone()))
+            .collect();
+        assert_eq!(groups, vec![("Firefox", vec![0, 2]), ("mpv", vec![1])]);
+    }
+
+    #[test]
+    fn scales_every_stream_by_the_same_ratio() {
+        let mut scaling = Scaling::new("Firefox", vec![(1, 40000), (2, 20000)]);
+
+        assert_eq!(scaling.scale(20000), vec![(1, 20000), (2, 10000)]);
+        assert_eq!(scaling.scale(60000), vec![(1, 60000), (2, 30000)]);
+    }
+
+    #[test]
+    fn keeps_the_balance_through_silence() {
+        let mut scaling = Scaling::new("Firefox", vec![(1, 40000), (2, 20000)]);
+
+        assert_eq!(scaling.scale(0), vec![(1, 0), (2, 0)]);
+        assert_eq!(scaling.scale(40000), vec![(1, 40000), (2, 20000)]);
+    }
+
+    #[test]
+    fn moves_silent_streams_together() {
+        let mut scaling = Scaling::new("Firefox", vec![(1, 0), (2, 0)]);
+
+        assert_eq!(scaling.scale(30000), vec![(1, 30000), (2, 30000)]);
+    }
+
+    #[test]
+    fn continues_the_drag_of_the_same_group() {
+        let scaling = Scaling::new("Firefox", vec![(1, 40000)]);
+
+        assert!(scaling.continues("Firefox"));
+        assert!(!scaling.continues("mpv"));
+    }
+}
@@ -0,0 +1,231 @@
+//! Undo and redo of the changes made from the mixer window.
+use std::time::{Duration, Instant};
+
+use crate::updater::{Change, Target};
+
+/// The longest pause between two slider moves of a single drag.
+const COALESCE_INTERVAL: Duration = Duration::from_millis(800);
+const MAX_ENTRIES: usize = 100;
+
+/// A change of a stream or device.
+#[derive(Debug, Clone)]
+pub struct Entry {
+    pub target: Target,
+    /// The name of the stream or device, for the undo list.
+    pub label: String,
+    pub before: Change,
+    pub after: Change,
+    changed_at: Instant,
+}
+
+impl Entry {
+    pub fn describe(&self) -> String {
+        match (self.before, self.after) {
+            (Change::Volume(before), Change::Volume(after)) => format!(
+                "{}: {}% -> {}%",
+                self.label,
+                percent(before),
+                percent(after)
+            ),
+            (_, Change::Mute(true)) => format!("{}: muted", self.label),
+            _ => format!("{}: unmuted", self.label),
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct History {
+    undo: Vec<Entry>,
+    redo: Vec<Entry>,
+}
+
+impl History {
+    /// Records a change, merging the moves of a slider drag into one entry.
+    ///
+    /// The drag of a group moves several streams in turn, so the entry of
+    /// the drag is looked for among all the recent ones.
+    pub fn record(&mut self, target: Target, label: String, before: Change, after: Change) {
+        self.redo.clear();
+
+        if let Change::Volume(_) = after {
+            let drag = self
+                .undo
+                .iter_mut()
+                .rev()
+                .take_while(|entry| entry.changed_at.elapsed() < COALESCE_INTERVAL)
+                .find(|entry| entry.target == target);
+
+            if let Some(entry) = drag {
+                if let Change::Volume(_) = entry.after {
+                    entry.after = after;
+                    entry.changed_at = Instant::now();
+                    return;
+                }
+            }
+        }
+
+        self.undo.push(Entry {
+            target,
+            label,
+            before,
+            after,
+            changed_at: Instant::now(),
+        });
+        if self.undo.len() > MAX_ENTRIES {
+            self.undo.remove(0);
+        }
+    }
+
+    /// Takes the last change, whose `before` value is to be restored.
+    pub fn undo(&mut self) -> Option<Entry> {
+        let entry = self.undo.pop()?;
+        self.redo.push(entry.clone());
+        Some(entry)
+    }
+
+    /// Takes the last undone change, whose `after` value is to be restored.
+    pub fn redo(&mut self) -> Option<Entry> {
+        let mut entry = self.redo.pop()?;
+        // A later move of the same slider must not be merged into it.
+        entry.changed_at = Instant::now()
+            .checked_sub(COALESCE_INTERVAL)
+            .unwrap_or(entry.changed_at);
+        self.undo.push(entry.clone());
+        Some(entry)
+    }
+
+    pub fn can_undo(&self) -> bool {
+        !self.undo.is_empty()
+    }
+
+    pub fn can_redo(&self) -> bool {
+        !self.redo.is_empty()
+    }
+
+    /// The changes which can be undone, the latest first.
+    pub fn entries(&self) -> impl Iterator<Item = &Entry> {
+        self.undo.iter().rev()
+    }
+}
+
+fn percent(volume: u32) -> u32 {
+    volume * 100 / 65536
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn volume(history: &mut History, target: Target, before: u32, after: u32) {
+        let (before, after) = (Change::Volume(before), Change::Volume(after));
+        history.record(target, "Stream".into(), before, after);
+    }
+
+    fn mute(history: &mut History, target: Target, mute: bool) {
+        let (before, after) = (Change::Mute(!mute), Change::Mute(mute));
+        history.record(target, "Stream".into(), before, after);
+    }
+
+    #[test]
+    fn merges_the_moves_of_a_drag() {
+        let mut history = History::default();
+        volume(&mut history, Target::SinkInput(1), 100, 200);
+        volume(&mut history, Target::SinkInput(1), 200, 300);
+        volume(&mut history, Target::SinkInput(1), 300, 400);
+
+        let entry = history.undo().unwrap();
+        assert_eq!(entry.before, Change::Volume(100));
+        assert_eq!(entry.after, Change::Volume(400));
+        assert!(!history.can_undo());
+    }
+
+    #[test]
+    fn merges_the_interleaved_moves_of_a_group_drag() {
+        let mut history = History::default();
+        for &after in &[200, 300] {
+            volume(&mut history, Target::SinkInput(1), after - 100, after);
+            volume(&mut history, Target::SinkInput(2), after - 100, after);
+        }
+
+        let entries: Vec<_> = history
+            .entries()
+            .map(|entry| (entry.target, entry.before, entry.after))
+            .collect();
+        assert_eq!(
+            entries,
+            vec![
+                (
+                    Target::SinkInput(2),
+                    Change::Volume(100),
+                    Change::Volume(300)
+                ),
+                (
+                    Target::SinkInput(1),
+                    Change::Volume(100),
+                    Change::Volume(300)
+                ),
+            ]
+        );
+    }
+
+    #[test]
+    fn keeps_mutes_and_other_targets_apart() {
+        let mut history = History::default();
+        mute(&mut history, Target::Sink, true);
+        mute(&mut history, Target::Sink, false);
+        volume(&mut history, Target::Sink, 100, 200);
+        volume(&mut history, Target::Source, 100, 200);
+
+        assert_eq!(history.entries().count(), 4);
+    }
+
+    #[test]
+    fn does_not_merge_into_a_redone_change() {
+        let mut history = History::default();
+        volume(&mut history, Target::Sink, 100, 200);
+        history.undo().unwrap();
+        history.redo().unwrap();
+        volume(&mut history, Target::Sink, 200, 300);
+
+        assert_eq!(history.entries().count(), 2);
+    }
+
+    #[test]
+    fn undoes_and_redoes_in_order() {
+        let mut history = History::default();
+        volume(&mut history, Target::Sink, 100, 200);
+        mute(&mut history, Target::Source, true);
+
+        assert_eq!(history.undo().unwrap().target, Target::Source);
+        assert_eq!(history.undo().unwrap().target, Target::Sink);
+        assert!(history.undo().is_none());
+        assert_eq!(history.redo().unwrap().target, Target::Sink);
+        assert!(history.can_redo());
+
+        // A new change drops what is left to redo.
+        mute(&mut history, Target::Sink, true);
+        assert!(!history.can_redo());
+    }
+
+    #[test]
+    fn keeps_the_latest_entries() {
+        let mut history = History::default();
+        for id in 0..MAX_ENTRIES as u32 + 10 {
+            mute(&mut history, Target::SinkInput(id), true);
+        }
+
+        assert_eq!(history.entries().count(), MAX_ENTRIES);
+        let oldest = history.entries().last().unwrap();
+        assert_eq!(oldest.target, Target::SinkInput(10));
+    }
+
+    #[test]
+    fn describes_the_changes() {
+        let mut history = History::default();
+        volume(&mut history, Target::Sink, 65536, 32768);
+        mute(&mut history, Target::Source, true);
+
+        let descriptions: Vec<_> = history.entries().map(Entry::describe).collect();
+        assert_eq!(descriptions, vec!["Stream: muted", "Stream: 100% -> 50%"]);
+    }
+}
@@ -0,0 +1,313 @@
+//! Single instance of the mixer window.
+//!
+//! The first window listens on the `instance` socket of the runtime
+//! directory. Later launches connect to it, send their request as a line
+//! and exit, so a global hotkey bound to `volume-controller-ui toggle`
+//! shows and hides the mixer. Launches take the `instance.lock` file of the
+//! directory until the socket listens, so that two of them never both take
+//! the socket for a stale one.
+use std::fs::{self, File, OpenOptions};
+use std::io::{self, BufRead, BufReader, ErrorKind, Write};
+use std::os::unix::io::AsRawFd;
+use std::os::unix::net::{UnixListener, UnixStream};
+use std::path::{Path, PathBuf};
+use std::sync::mpsc::{self, Receiver, Sender};
+use std::thread;
+
+use crate::config;
+
+const SOCKET_FILE: &str = "instance";
+const LOCK_FILE: &str = "instance.lock";
+
+/// What a launch asks the mixer window for.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Request {
+    /// Open the window if it is not running, or show it if it is hidden.
+    Show,
+    /// Open or show the window, or hide the shown one.
+    Toggle,
+    /// Show the window and highlight the streams of an application.
+    Focus(String),
+    /// Press or release the push-to-talk trigger of the running window.
+    Talk(bool),
+}
+
+impl Request {
+    fn to_line(&self) -> String {
+        match self {
+            Request::Show => "show\n".into(),
+            Request::Toggle => "toggle\n".into(),
+            Request::Focus(application) => format!("focus {}\n", application),
+            Request::Talk(true) => "ptt press\n".into(),
+            Request::Talk(false) => "ptt release\n".into(),
+        }
+    }
+
+    fn from_line(line: &str) -> Option<Self> {
+        let mut words = line.trim_end().splitn(2, ' ');
+
+        match (words.next()?, words.next()) {
+            ("show", None) => Some(Request::Show),
+            ("toggle", None) => Some(Request::Toggle),
+            ("focus", Some(application)) => Some(Request::Focus(application.into())),
+            ("ptt", Some("press")) => Some(Request::Talk(true)),
+            ("ptt", Some("release")) => Some(Request::Talk(false)),
+            _ => None,
+        }
+    }
+}
+
+/// The outcome of [`acquire`].
+///
+/// [`acquire`]: fn.acquire.html
+pub enum Instance {
+    /// The request was sent to the running window.
+    Forwarded,
+    /// This process is the running window; it gets the requests of the
+    /// later launches, starting with its own focus request. The sender
+    /// lets other parts of the process make requests as well.
+    Primary(Sender<Request>, Receiver<Request>),
+}
+
+/// Sends `request` to the running window, or becomes the running window.
+pub fn acquire(request: Request) -> Instance {
+    match config::runtime_directory() {
+        Some(directory) => acquire_in(&directory, request),
+        None => {
+            let (sender, requests) = mpsc::channel();
+            opened(request, &sender);
+            Instance::Primary(sender, requests)
+        }
+    }
+}
+
+fn acquire_in(directory: &Path, request: Request) -> Instance {
+    let (sender, requests) = mpsc::channel();
+
+    let lock = match Lock::take(directory) {
+        Ok(lock) => Some(lock),
+        Err(e) => {
+            log::error!("Unable to lock {}: {}", directory.display(), e);
+            None
+        }
+    };
+
+    let path = directory.join(SOCKET_FILE);
+    match send(&path, &request) {
+        Ok(()) => return Instance::Forwarded,
+        // Left behind by a window which did not exit cleanly. Only the
+        // holder of the lock may tell, as another launch may be about to
+        // listen on it otherwise.
+        Err(ref e) if e.kind() == ErrorKind::ConnectionRefused && lock.is_some() => {
+            if let Err(e) = fs::remove_file(&path) {
+                log::error!("Unable to remove {}: {}", path.display(), e);
+            }
+        }
+        Err(ref e) if is_absent(e) => {}
+        Err(e) => log::error!("Unable to reach the running window: {}", e),
+    }
+    opened(request, &sender);
+
+    let listener_sender = sender.clone();
+    match UnixListener::bind(&path) {
+        Ok(listener) => {
+            thread::spawn(move || accept(listener, listener_sender));
+        }
+        Err(e) => log::error!("Unable to listen on {}: {}", path.display(), e),
+    }
+    // The later launches now reach this window.
+    drop(lock);
+
+    Instance::Primary(sender, requests)
+}
+
+/// Sends `request` to the running window, returning `false` when there is
+/// none.
+pub fn forward(request: &Request) -> bool {
+    let path = match socket() {
+        Some(path) => path,
+        None => return false,
+    };
+
+    match send(&path, request) {
+        Ok(()) => true,
+        Err(ref e) if is_absent(e) => false,
+        Err(e) => {
+            log::error!("Unable to reach the running window: {}", e);
+            false
+        }
+    }
+}
+
+fn socket() -> Option<PathBuf> {
+    config::runtime_directory().map(|directory| directory.join(SOCKET_FILE))
+}
+
+fn send(path: &Path, request: &Request) -> io::Result<()> {
+    let mut stream = UnixStream::connect(path)?;
+    stream.write_all(request.to_line().as_bytes())
+}
+
+/// Whether connecting failed for want of a running window.
+fn is_absent(error: &io::Error) -> bool {
+    matches!(
+        error.kind(),
+        ErrorKind::NotFound | ErrorKind::ConnectionRefused
+    )
+}
+
+/// An exclusive `flock` on the lock file of the runtime directory, released
+/// when dropped.
+struct Lock {
+    _file: File,
+}
+
+impl Lock {
+    /// Waits until no other launch holds the lock.
+    fn take(directory: &Path) -> io::Result<Self> {
+        fs::create_dir_all(directory)?;
+        let file = OpenOptions::new()
+            .create(true)
+            .truncate(false)
+            .write(true)
+            .open(directory.join(LOCK_FILE))?;
+
+        loop {
+            if unsafe { libc::flock(file.as_raw_fd(), libc::LOCK_EX) } == 0 {
+                return Ok(Self { _file: file });
+            }
+            let error = io::Error::last_os_error();
+            if error.kind() != ErrorKind::Interrupted {
+                return Err(error);
+            }
+        }
+    }
+}
+
+/// Passes on what is left of `request` once the window is open.
+fn opened(request: Request, requests: &Sender<Request>) {
+    if let Request::Focus(_) = request {
+        let _ = requests.send(request);
+    }
+}
+
+fn accept(listener: UnixListener, requests: Sender<Request>) {
+    for stream in listener.incoming() {
+        let stream = match stream {
+            Ok(stream) => stream,
+            Err(e) => {
+                log::error!("Unable to accept a launch request: {}", e);
+                continue;
+            }
+        };
+
+        let mut line = String::new();
+        if let Err(e) = BufReader::new(stream).read_line(&mut line) {
+            log::error!("Unable to read a launch request: {}", e);
+            continue;
+        }
+
+        match Request::from_line(&line) {
+            Some(request) => {
+                if requests.send(request).is_err() {
+                    return;
+                }
+            }
+            None => log::warn!("Invalid launch request \"{}\"", line.trim_end()),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::env;
+    use std::process;
+    use std::sync::{Arc, Barrier};
+    use std::time::Duration;
+
+    const WAIT: Duration = Duration::from_secs(5);
+
+    /// A runtime directory of its own for a test, removed at the end.
+    struct Directory(PathBuf);
+
+    impl Directory {
+        fn new(test: &str) -> Self {
+            let path =
+                env::temp_dir().join(format!("volume-controller-{}-{}", test, process::id()));
+            let _ = fs::remove_dir_all(&path);
+            Self(path)
+        }
+    }
+
+    impl Drop for Directory {
+        fn drop(&mut self) {
+            let _ = fs::remove_dir_all(&self.0);
+        }
+    }
+
+    #[test]
+    fn requests_round_trip_through_their_lines() {
+        let requests = vec![
+            Request::Show,
+            Request::Toggle,
+            Request::Focus("Firefox Web".into()),
+            Request::Talk(true),
+            Request::Talk(false),
+        ];
+        for request in requests {
+            assert_eq!(Request::from_line(&request.to_line()), Some(request));
+        }
+        assert_eq!(Request::from_line("focus\n"), None);
+        assert_eq!(Request::from_line("quit\n"), None);
+    }
+
+    #[test]
+    fn only_one_of_two_launches_becomes_the_window() {
+        let directory = Directory::new("two-launches");
+        let barrier = Arc::new(Barrier::new(2));
+
+        let launches: Vec<_> = vec![Request::Show, Request::Toggle]
+            .into_iter()
+            .map(|request| {
+                let directory = directory.0.clone();
+                let barrier = barrier.clone();
+                thread::spawn(move || {
+                    barrier.wait();
+                    acquire_in(&directory, request)
+                })
+            })
+            .collect();
+        let mut primaries: Vec<Receiver<Request>> = launches
+            .into_iter()
+            .filter_map(|launch| match launch.join().unwrap() {
+                Instance::Primary(_, requests) => Some(requests),
+                Instance::Forwarded => None,
+            })
+            .collect();
+
+        assert_eq!(primaries.len(), 1);
+        let request = primaries.pop().unwrap().recv_timeout(WAIT).unwrap();
+        assert!(request == Request::Show || request == Request::Toggle);
+    }
+
+    #[test]
+    fn takes_over_the_socket_of_a_window_which_did_not_exit_cleanly() {
+        let directory = Directory::new("stale-socket");
+        fs::create_dir_all(&directory.0).unwrap();
+        drop(UnixListener::bind(directory.0.join(SOCKET_FILE)).unwrap());
+
+        let requests = match acquire_in(&directory.0, Request::Show) {
+            Instance::Primary(_, requests) => requests,
+            Instance::Forwarded => panic!("forwarded to a stale socket"),
+        };
+        match acquire_in(&directory.0, Request::Focus("mpv".into())) {
+            Instance::Forwarded => {}
+            Instance::Primary(..) => panic!("a second window was opened"),
+        }
+        assert_eq!(
+            requests.recv_timeout(WAIT),
+            Ok(Request::Focus("mpv".into()))
+        );
+    }
+}
@@ -0,0 +1,161 @@
+//! Arrangements of the mixer rows and the sizes they use.
+use serde::Deserialize;
+
+/// Approximate width of a glyph, relative to the text size.
+const GLYPH_RATIO: f32 = 0.6;
+const ELLIPSIS: char = '\u{2026}';
+
+/// The width of a column of the [`Layout::Vertical`] layout.
+///
+/// [`Layout::Vertical`]: enum.Layout.html#variant.Vertical
+pub const COLUMN_WIDTH: u16 = 100;
+/// The height of the sliders of the [`Layout::Vertical`] layout.
+///
+/// [`Layout::Vertical`]: enum.Layout.html#variant.Vertical
+pub const VERTICAL_SLIDER_HEIGHT: u16 = 200;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum Layout {
+    /// A row per stream: name, slider, volume and mute button.
+    Horizontal,
+    /// A dense single line per stream, fit for a small popup.
+    Compact,
+    /// A mixing desk: a column per stream with a vertical slider.
+    Vertical,
+}
+
+impl Default for Layout {
+    fn default() -> Self {
+        Layout::Horizontal
+    }
+}
+
+impl Layout {
+    pub const ALL: [Layout; 3] = [Layout::Horizontal, Layout::Compact, Layout::Vertical];
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Layout::Horizontal => "Horizontal",
+            Layout::Compact => "Compact",
+            Layout::Vertical => "Mixer",
+        }
+    }
+
+    pub fn text_size(self) -> u16 {
+        match self {
+            Layout::Compact => 14,
+            Layout::Horizontal | Layout::Vertical => 20,
+        }
+    }
+
+    pub fn spacing(self) -> u16 {
+        match self {
+            Layout::Compact => 4,
+            Layout::Horizontal | Layout::Vertical => 10,
+        }
+    }
+
+    pub fn padding(self) -> u16 {
+        match self {
+            Layout::Compact => 6,
+            Layout::Horizontal | Layout::Vertical => 20,
+        }
+    }
+
+    /// The widest a stream name may get before being elided.
+    pub fn max_name_width(self) -> u16 {
+        match self {
+            Layout::Horizontal => 300,
+            Layout::Compact => 120,
+            Layout::Vertical => COLUMN_WIDTH,
+        }
+    }
+
+    pub fn status_width(self) -> u16 {
+        text_width("100%\u{2026}", self.text_size())
+    }
+
+    pub fn toggle_width(self) -> u16 {
+        match self {
+            Layout::Horizontal => 140,
+            Layout::Compact => 36,
+            Layout::Vertical => COLUMN_WIDTH / 2,
+        }
+    }
+
+    pub fn toggle_padding(self) -> u16 {
+        match self {
+            Layout::Horizontal => 10,
+            Layout::Compact | Layout::Vertical => 4,
+        }
+    }
+}
+
+/// Estimates the width of `text` drawn with the given size.
+pub fn text_width(text: &str, size: u16) -> u16 {
+    (text.chars().count() as f32 * f32::from(size) * GLYPH_RATIO).ceil() as u16
+}
+
+/// The width of the name column: the widest of `names`, capped at `max`.
+pub fn name_width<'a, I>(names: I, size: u16, max: u16) -> u16
+where
+    I: IntoIterator<Item = &'a str>,
+{
+    names
+        .into_iter()
+        .map(|name| text_width(name, size))
+        .max()
+        .unwrap_or(0)
+        .min(max)
+}
+
+/// Shortens `name` with an ellipsis so that it fits in `width`.
+pub fn elide(name: &str, width: u16, size: u16) -> String {
+    let max_chars = (f32::from(width) / (f32::from(size) * GLYPH_RATIO)).floor() as usize;
+
+    if name.chars().count() <= max_chars {
+        return name.to_owned();
+    }
+
+    let mut elided: String = name.chars().take(max_chars.saturating_sub(1)).collect();
+    elided.push(ELLIPSIS);
+    elided
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn measures_text_by_characters_rather_than_bytes() {
+        assert_eq!(text_width("", 20), 0);
+        assert_eq!(text_width("Firefox", 20), 84);
+        assert_eq!(text_width("Écoute", 20), 72);
+        assert_eq!(text_width("音楽", 20), 24);
+    }
+
+    #[test]
+    fn sizes_the_name_column_for_the_widest_name_up_to_the_cap() {
+        let names = ["mpv", "Firefox"];
+
+        assert_eq!(name_width(names.iter().cloned(), 20, 300), 84);
+        assert_eq!(name_width(names.iter().cloned(), 20, 50), 50);
+        assert_eq!(name_width(Vec::new(), 20, 300), 0);
+    }
+
+    #[test]
+    fn keeps_the_names_which_fit() {
+        assert_eq!(elide("mpv", 120, 20), "mpv");
+        assert_eq!(elide("Firefox", 84, 20), "Firefox");
+        assert_eq!(elide("音楽", 120, 20), "音楽");
+    }
+
+    #[test]
+    fn elides_the_names_which_do_not_fit() {
+        assert_eq!(elide("Spotify Premium", 120, 20), "Spotify P\u{2026}");
+        assert_eq!(elide("Écoute en ligne", 72, 20), "Écout\u{2026}");
+        assert_eq!(elide("音楽プレーヤー", 48, 20), "音楽プ\u{2026}");
+        assert_eq!(elide("Firefox", 6, 20), "\u{2026}");
+    }
+}
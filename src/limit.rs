@@ -0,0 +1,99 @@
+//! Hard caps on the volume of sinks and applications.
+//!
+//! The caps are read from the `[limits]` section of the configuration file,
+//! in percent, by sink name and by application name:
+//!
+//! ```toml
+//! [limits.sinks]
+//! "alsa_output.usb-headset.analog-stereo" = 60
+//!
+//! [limits.applications]
+//! Firefox = 80
+//! ```
+//!
+//! The mixer, its D-Bus service and the tray icon keep their own changes
+//! under the caps, and the mixer brings back the volumes other programs
+//! raise above them.
+use std::collections::HashMap;
+
+use serde::Deserialize;
+
+/// The volume of 100%.
+const NORMAL_VOLUME: u64 = 65536;
+
+/// The `[limits]` section of the configuration file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct LimitConfig {
+    /// The caps of the sinks, in percent.
+    pub sinks: HashMap<String, u32>,
+    /// The caps of the streams of applications, in percent.
+    pub applications: HashMap<String, u32>,
+}
+
+impl LimitConfig {
+    /// The highest volume of the sink, if it is capped.
+    pub fn sink(&self, name: &str) -> Option<u32> {
+        self.sinks.get(name).map(|&percent| volume(percent))
+    }
+
+    /// The highest volume of the streams of the application, if they are
+    /// capped.
+    pub fn application(&self, name: &str) -> Option<u32> {
+        self.applications.get(name).map(|&percent| volume(percent))
+    }
+}
+
+fn volume(percent: u32) -> u32 {
+    (u64::from(percent) * NORMAL_VOLUME / 100) as u32
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn config() -> LimitConfig {
+        toml::from_str(
+            r#"
+            [sinks]
+            "alsa_output.usb-headset.analog-stereo" = 60
+
+            [applications]
+            Firefox = 80
+            mpv = 150
+            Silent = 0
+            "#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn caps_the_listed_sinks_in_percent() {
+        let config = config();
+
+        assert_eq!(
+            config.sink("alsa_output.usb-headset.analog-stereo"),
+            Some(39321)
+        );
+        assert_eq!(config.sink("alsa_output.pci.analog-stereo"), None);
+    }
+
+    #[test]
+    fn caps_the_listed_applications_in_percent() {
+        let config = config();
+
+        // Rounded down, to stay under the cap.
+        assert_eq!(config.application("Firefox"), Some(52428));
+        assert_eq!(config.application("mpv"), Some(98304));
+        assert_eq!(config.application("Silent"), Some(0));
+        assert_eq!(config.application("firefox"), None);
+    }
+
+    #[test]
+    fn caps_nothing_by_default() {
+        let config: LimitConfig = toml::from_str("").unwrap();
+
+        assert!(config.sinks.is_empty());
+        assert!(config.applications.is_empty());
+    }
+}
@@ -0,0 +1,222 @@
+//! Rows bound together, so that they move as one.
+//!
+//! A change of one row of a link follows to the others: the same volume
+//! in the absolute mode, or the same difference in the relative one, which
+//! keeps their balance. Muting follows in both modes. The links hold the
+//! streams by id, so they only last as long as the streams do.
+//!
+//! Only the rows of the mixer can be linked: the streams, the default sink
+//! and the default source. Linking two sinks, such as speakers and a
+//! subwoofer, is out of scope, as the mixer has no rows for the other
+//! devices; a combined sink from the devices page plays to both instead.
+use crate::updater::{Change, Target};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LinkMode {
+    Absolute,
+    Relative,
+}
+
+#[derive(Debug, Clone)]
+pub struct Link {
+    pub mode: LinkMode,
+    pub targets: Vec<Target>,
+}
+
+#[derive(Debug, Default)]
+pub struct Links {
+    links: Vec<Link>,
+}
+
+impl Links {
+    /// Links the targets, taking them out of their former links.
+    pub fn add(&mut self, targets: Vec<Target>, mode: LinkMode) {
+        for &target in &targets {
+            self.unlink(target);
+        }
+        if targets.len() > 1 {
+            self.links.push(Link { mode, targets });
+        }
+    }
+
+    /// Takes the target out of its link, dropping the link when it would
+    /// bind a single target.
+    pub fn unlink(&mut self, target: Target) {
+        self.retain(|linked| linked != target);
+    }
+
+    /// Keeps the targets `f` accepts, such as the streams still playing.
+    pub fn retain<F>(&mut self, f: F)
+    where
+        F: Fn(Target) -> bool,
+    {
+        for link in &mut self.links {
+            link.targets.retain(|&target| f(target));
+        }
+        self.links.retain(|link| link.targets.len() > 1);
+    }
+
+    /// The number of the link of the target, counted from 1 for display.
+    pub fn number(&self, target: Target) -> Option<usize> {
+        self.links
+            .iter()
+            .position(|link| link.targets.contains(&target))
+            .map(|position| position + 1)
+    }
+
+    /// The changes following `change` of `target`, whose value was
+    /// `previous`. `current` gives the volume of the other targets.
+    pub fn follow<F>(
+        &self,
+        target: Target,
+        previous: Change,
+        change: Change,
+        max_volume: u32,
+        current: F,
+    ) -> Vec<(Target, Change)>
+    where
+        F: Fn(Target) -> Option<u32>,
+    {
+        let link = match self
+            .links
+            .iter()
+            .find(|link| link.targets.contains(&target))
+        {
+            Some(link) => link,
+            None => return Vec::new(),
+        };
+        let others = link.targets.iter().filter(|&&other| other != target);
+
+        match (change, previous, link.mode) {
+            (Change::Mute(mute), _, _) => {
+                others.map(|&other| (other, Change::Mute(mute))).collect()
+            }
+            (Change::Volume(volume), _, LinkMode::Absolute) => others
+                .map(|&other| (other, Change::Volume(volume)))
+                .collect(),
+            (Change::Volume(volume), Change::Volume(previous), LinkMode::Relative) => {
+                let difference = i64::from(volume) - i64::from(previous);
+                others
+                    .filter_map(|&other| {
+                        let moved = i64::from(current(other)?) + difference;
+                        let moved = moved.max(0).min(i64::from(max_volume)) as u32;
+                        Some((other, Change::Volume(moved)))
+                    })
+                    .collect()
+            }
+            (Change::Volume(_), Change::Mute(_), LinkMode::Relative) => Vec::new(),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const MAX_VOLUME: u32 = 65536;
+    const A: Target = Target::SinkInput(1);
+    const B: Target = Target::SinkInput(2);
+    const C: Target = Target::SinkInput(3);
+
+    fn linked(targets: &[Target], mode: LinkMode) -> Links {
+        let mut links = Links::default();
+        links.add(targets.to_vec(), mode);
+        links
+    }
+
+    /// The changes following a volume change of `target`. The volumes of B
+    /// and C are 10000 and 60000; the others are unknown.
+    fn volume(links: &Links, target: Target, previous: u32, volume: u32) -> Vec<(Target, Change)> {
+        let (previous, change) = (Change::Volume(previous), Change::Volume(volume));
+        links.follow(target, previous, change, MAX_VOLUME, |other| match other {
+            B => Some(10000),
+            C => Some(60000),
+            _ => None,
+        })
+    }
+
+    fn mute(links: &Links, target: Target) -> Vec<(Target, Change)> {
+        links.follow(
+            target,
+            Change::Mute(false),
+            Change::Mute(true),
+            MAX_VOLUME,
+            |_| None,
+        )
+    }
+
+    #[test]
+    fn follows_the_same_volume_in_the_absolute_mode() {
+        let links = linked(&[A, B, C], LinkMode::Absolute);
+
+        assert_eq!(
+            volume(&links, A, 0, 30000),
+            vec![(B, Change::Volume(30000)), (C, Change::Volume(30000))]
+        );
+    }
+
+    #[test]
+    fn follows_the_difference_in_the_relative_mode_within_bounds() {
+        let links = linked(&[A, B, C], LinkMode::Relative);
+
+        assert_eq!(
+            volume(&links, A, 20000, 30000),
+            vec![(B, Change::Volume(20000)), (C, Change::Volume(MAX_VOLUME))]
+        );
+        assert_eq!(
+            volume(&links, A, 30000, 15000),
+            vec![(B, Change::Volume(0)), (C, Change::Volume(45000))]
+        );
+    }
+
+    #[test]
+    fn follows_mutes_in_both_modes() {
+        for &mode in &[LinkMode::Absolute, LinkMode::Relative] {
+            let links = linked(&[A, B], mode);
+
+            assert_eq!(mute(&links, A), vec![(B, Change::Mute(true))]);
+        }
+    }
+
+    #[test]
+    fn skips_the_targets_without_a_known_volume() {
+        let links = linked(&[B, Target::Sink], LinkMode::Relative);
+
+        assert!(volume(&links, B, 10000, 20000).is_empty());
+    }
+
+    #[test]
+    fn leaves_the_targets_out_of_links_alone() {
+        let links = linked(&[A, B], LinkMode::Absolute);
+
+        assert!(volume(&links, C, 0, 30000).is_empty());
+    }
+
+    #[test]
+    fn never_follows_back_to_the_changed_target() {
+        // Linking a target again, in another order or to others, must not
+        // leave it in two links, whose changes would go round in a cycle.
+        let mut links = linked(&[A, B], LinkMode::Absolute);
+        links.add(vec![B, A], LinkMode::Absolute);
+        links.add(vec![B, C], LinkMode::Absolute);
+        links.add(vec![C, A], LinkMode::Relative);
+
+        for &target in &[A, B, C] {
+            assert!(mute(&links, target)
+                .iter()
+                .all(|&(other, _)| other != target));
+        }
+        assert_eq!(links.number(A), links.number(C));
+        assert_eq!(links.number(B), None);
+    }
+
+    #[test]
+    fn drops_the_links_left_with_a_single_target() {
+        let mut links = linked(&[A, B], LinkMode::Absolute);
+        links.add(vec![B, C], LinkMode::Absolute);
+        links.retain(|target| target != C);
+
+        assert_eq!(links.number(A), None);
+        assert_eq!(links.number(B), None);
+    }
+}
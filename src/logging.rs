@@ -0,0 +1,119 @@
+//! Log output of the application.
+//!
+//! The level comes from `--log-level`, then from the
+//! `VOLUME_CONTROLLER_LOG` variable, and is `debug` in debug builds and
+//! `warn` otherwise. The lines go to standard error, or are appended to the
+//! file given with `--log-file` or `VOLUME_CONTROLLER_LOG_FILE`. The start
+//! and the end of the server operations are logged at the `trace` level.
+use std::env;
+use std::fs::{File, OpenOptions};
+use std::io::{self, Write};
+use std::path::PathBuf;
+use std::sync::Mutex;
+use std::time::Instant;
+
+use log::{LevelFilter, Log, Metadata, Record};
+
+const LEVEL_VARIABLE: &str = "VOLUME_CONTROLLER_LOG";
+const FILE_VARIABLE: &str = "VOLUME_CONTROLLER_LOG_FILE";
+
+/// The logging options of the command line.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct LogOptions {
+    pub level: Option<LevelFilter>,
+    pub file: Option<PathBuf>,
+}
+
+enum Output {
+    Stderr,
+    File(File),
+}
+
+struct Logger {
+    level: LevelFilter,
+    output: Mutex<Output>,
+    started_at: Instant,
+}
+
+impl Log for Logger {
+    fn enabled(&self, metadata: &Metadata) -> bool {
+        metadata.level() <= self.level
+    }
+
+    fn log(&self, record: &Record) {
+        if !self.enabled(record.metadata()) {
+            return;
+        }
+
+        let line = format!(
+            "[{:>9.3} {:<5} {}] {}\n",
+            self.started_at.elapsed().as_secs_f64(),
+            record.level(),
+            record.target(),
+            record.args()
+        );
+        let mut output = match self.output.lock() {
+            Ok(output) => output,
+            Err(poisoned) => poisoned.into_inner(),
+        };
+        // A failed write has nowhere to be reported.
+        let _ = match &mut *output {
+            Output::Stderr => io::stderr().write_all(line.as_bytes()),
+            Output::File(file) => file.write_all(line.as_bytes()),
+        };
+    }
+
+    fn flush(&self) {
+        if let Ok(mut output) = self.output.lock() {
+            if let Output::File(file) = &mut *output {
+                let _ = file.flush();
+            }
+        }
+    }
+}
+
+/// Installs the logger. Called once, before anything is logged.
+pub fn init(options: LogOptions) {
+    let level = options
+        .level
+        .or_else(|| {
+            let value = env::var(LEVEL_VARIABLE).ok()?;
+            let level = parse_level(&value);
+            if level.is_none() {
+                eprintln!("Invalid log level \"{}\" in {}", value, LEVEL_VARIABLE);
+            }
+            level
+        })
+        .unwrap_or(if cfg!(debug_assertions) {
+            LevelFilter::Debug
+        } else {
+            LevelFilter::Warn
+        });
+
+    let path = options
+        .file
+        .or_else(|| env::var_os(FILE_VARIABLE).map(PathBuf::from));
+    let output = match path {
+        Some(path) => match OpenOptions::new().create(true).append(true).open(&path) {
+            Ok(file) => Output::File(file),
+            Err(e) => {
+                eprintln!("Unable to open the log file {}: {}", path.display(), e);
+                Output::Stderr
+            }
+        },
+        None => Output::Stderr,
+    };
+
+    let logger = Logger {
+        level,
+        output: Mutex::new(output),
+        started_at: Instant::now(),
+    };
+    if log::set_boxed_logger(Box::new(logger)).is_ok() {
+        log::set_max_level(level);
+    }
+}
+
+pub fn parse_level(value: &str) -> Option<LevelFilter> {
+    value.trim().parse().ok()
+}
@@ -1,9 +1,97 @@
 mod button;
+mod cli;
+mod clipboard;
+mod config;
+mod ducking;
+mod fade;
+mod filter;
+mod group;
+mod history;
+mod instance;
+mod layout;
+mod limit;
+mod link;
+mod logging;
+mod modules;
+mod mpris;
+mod osd;
+mod ptt;
+mod scene;
+mod service;
+mod sleep;
+mod theme;
+mod tray;
 mod ui;
 mod updater;
+mod vertical_slider;
 
-use iced::{Sandbox, Settings};
+use std::env;
+use std::process;
+
+use iced::{Application, Settings};
+
+use cli::Mode;
+use config::Config;
+use instance::Instance;
+use layout::Layout;
+
+const POPUP_SIZE: (u32, u32) = (420, 260);
 
 fn main() {
-    ui::UserInterface::run(Settings::default());
+    let (mode, log_options) = cli::parse(env::args().skip(1)).unwrap_or_else(|e| {
+        eprintln!("{}\n\n{}", e, cli::USAGE);
+        process::exit(2);
+    });
+    logging::init(log_options);
+
+    match mode {
+        Mode::Window(request) => {
+            let (sender, requests) = match instance::acquire(request) {
+                Instance::Forwarded => return,
+                Instance::Primary(sender, requests) => (sender, requests),
+            };
+
+            service::spawn(sender);
+            ui::UserInterface::run(Settings::with_flags(ui::Flags {
+                config: Config::load(),
+                requests: Some(requests),
+            }));
+        }
+        Mode::Popup => {
+            let config = Config {
+                layout: Layout::Compact,
+                ..Config::load()
+            };
+            let mut settings = Settings::with_flags(ui::Flags {
+                config,
+                requests: None,
+            });
+            settings.window.size = POPUP_SIZE;
+            settings.window.decorations = false;
+
+            ui::UserInterface::run(settings);
+        }
+        Mode::Forward(request) => {
+            if !instance::forward(&request) {
+                eprintln!("The mixer window is not running");
+                process::exit(1);
+            }
+        }
+        Mode::Scene(command) => {
+            if let Err(e) = scene::run(command) {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        }
+        Mode::Fade(command) => {
+            if let Err(e) = fade::run(command) {
+                eprintln!("{}", e);
+                process::exit(1);
+            }
+        }
+        Mode::Tray => tray::run(),
+        Mode::Osd => osd::watch(),
+        Mode::OsdShow(device) => osd::show(device),
+        Mode::Help => println!("{}", cli::USAGE),
+    }
 }
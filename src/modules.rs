@@ -0,0 +1,336 @@
+//! Virtual devices created by loading server modules.
+//!
+//! The modules loaded from the mixer are tracked in `modules.toml` of the
+//! configuration directory, so that they can be removed later and, when
+//! asked, loaded again at the next start.
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config;
+use crate::updater::{fetch_modules, load_module, unload_module, PulseHandler};
+
+const MODULES_FILE: &str = "modules.toml";
+
+/// A module setup the mixer knows how to create.
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "kind", rename_all = "kebab-case")]
+pub enum Setup {
+    /// A sink playing into nothing, whose monitor can be recorded.
+    NullSink { name: String },
+    /// Plays a source into a sink.
+    Loopback { source: String, sink: String },
+    /// A sink playing into several others.
+    CombineSink { name: String, sinks: Vec<String> },
+    /// A source with the echo of a sink removed.
+    EchoCancel {
+        name: String,
+        source: String,
+        sink: String,
+    },
+}
+
+impl Setup {
+    /// The module to load and its argument.
+    pub fn module(&self) -> (&'static str, String) {
+        match self {
+            Setup::NullSink { name } => (
+                "module-null-sink",
+                format!("sink_name={0} sink_properties=device.description={0}", name),
+            ),
+            Setup::Loopback { source, sink } => (
+                "module-loopback",
+                format!("source={} sink={}", source, sink),
+            ),
+            Setup::CombineSink { name, sinks } => (
+                "module-combine-sink",
+                format!("sink_name={} slaves={}", name, sinks.join(",")),
+            ),
+            Setup::EchoCancel { name, source, sink } => (
+                "module-echo-cancel",
+                format!(
+                    "source_name={} source_master={} sink_master={}",
+                    name, source, sink
+                ),
+            ),
+        }
+    }
+
+    pub fn describe(&self) -> String {
+        match self {
+            Setup::NullSink { name } => format!("Null sink {}", name),
+            Setup::Loopback { source, sink } => format!("Loopback {} -> {}", source, sink),
+            Setup::CombineSink { name, sinks } => {
+                format!("Combined sink {} over {}", name, sinks.join(", "))
+            }
+            Setup::EchoCancel { name, source, .. } => {
+                format!("Echo-cancelled source {} from {}", name, source)
+            }
+        }
+    }
+
+    /// Checks the names, which end up unquoted in the module argument.
+    pub fn validate(&self) -> Result<(), String> {
+        let names: Vec<&String> = match self {
+            Setup::NullSink { name } => vec![name],
+            Setup::Loopback { source, sink } => vec![source, sink],
+            Setup::CombineSink { name, sinks } => std::iter::once(name).chain(sinks).collect(),
+            Setup::EchoCancel { name, source, sink } => vec![name, source, sink],
+        };
+
+        for name in names {
+            let is_valid = !name.is_empty()
+                && name
+                    .chars()
+                    .all(|c| c.is_ascii_alphanumeric() || "_.-".contains(c));
+            if !is_valid {
+                return Err(format!("Invalid device name \"{}\"", name));
+            }
+        }
+        Ok(())
+    }
+}
+
+/// A module loaded from the mixer.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub struct Tracked {
+    /// Load it again at the next start.
+    pub recreate: bool,
+    /// The index of the loaded module, which changes with every load.
+    pub index: Option<u32>,
+    // Last, as TOML writes tables after the plain values.
+    pub setup: Setup,
+}
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+struct ModulesFile {
+    #[serde(default)]
+    modules: Vec<Tracked>,
+}
+
+/// Loads a setup and tracks it.
+pub fn create(
+    handler: &mut PulseHandler,
+    tracked: &mut Vec<Tracked>,
+    setup: Setup,
+    recreate: bool,
+) -> Result<(), String> {
+    setup.validate()?;
+
+    let (name, argument) = setup.module();
+    let index = load_module(handler, name, &argument)
+        .ok_or_else(|| format!("The server refused to load {}", name))?;
+
+    tracked.push(Tracked {
+        setup,
+        recreate,
+        index: Some(index),
+    });
+    save(tracked)
+}
+
+/// Unloads a tracked module and forgets it.
+pub fn remove(
+    handler: &mut PulseHandler,
+    tracked: &mut Vec<Tracked>,
+    position: usize,
+) -> Result<(), String> {
+    if let Some(index) = tracked[position].index {
+        if !unload_module(handler, index) {
+            return Err(format!("The server refused to unload module {}", index));
+        }
+    }
+
+    tracked.remove(position);
+    save(tracked)
+}
+
+/// Reads the tracked modules, loading again the ones to recreate which
+/// are gone and forgetting the others.
+pub fn restore(handler: &mut PulseHandler) -> Vec<Tracked> {
+    let loaded = fetch_modules(handler);
+    let mut tracked = Vec::new();
+
+    for mut module in load() {
+        let (name, argument) = module.setup.module();
+        let is_loaded = module.index.map_or(false, |index| {
+            loaded.iter().any(|loaded| {
+                loaded.index == index && loaded.name == name && loaded.argument == argument
+            })
+        });
+
+        if !is_loaded {
+            module.index = None;
+            if module.recreate {
+                module.index = load_module(handler, name, &argument);
+                if module.index.is_none() {
+                    log::warn!("Unable to recreate {}", module.setup.describe());
+                }
+            }
+        }
+        if module.index.is_some() || module.recreate {
+            tracked.push(module);
+        }
+    }
+
+    if let Err(e) = save(&tracked) {
+        log::error!("{}", e);
+    }
+    tracked
+}
+
+pub fn save(tracked: &[Tracked]) -> Result<(), String> {
+    let path = path().ok_or("No configuration directory")?;
+    let file = ModulesFile {
+        modules: tracked.to_vec(),
+    };
+    let content =
+        toml::to_string(&file).map_err(|e| format!("Unable to save the modules: {}", e))?;
+
+    path.parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(&path, content))
+        .map_err(|e| format!("Unable to write {}: {}", path.display(), e))
+}
+
+fn load() -> Vec<Tracked> {
+    let path = match path() {
+        Some(path) => path,
+        None => return Vec::new(),
+    };
+
+    match fs::read_to_string(&path) {
+        Ok(content) => match toml::from_str::<ModulesFile>(&content) {
+            Ok(file) => file.modules,
+            Err(e) => {
+                log::error!("Invalid modules file {}: {}", path.display(), e);
+                Vec::new()
+            }
+        },
+        Err(e) if e.kind() == ErrorKind::NotFound => Vec::new(),
+        Err(e) => {
+            log::error!("Unable to read {}: {}", path.display(), e);
+            Vec::new()
+        }
+    }
+}
+
+fn path() -> Option<PathBuf> {
+    config::directory().map(|directory| directory.join(MODULES_FILE))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const SINK: &str = "alsa_output.pci-0000_00_1f.3.analog-stereo";
+    const SOURCE: &str = "alsa_input.usb-Blue_Yeti-00.analog-stereo";
+
+    #[test]
+    fn builds_the_module_arguments() {
+        let null_sink = Setup::NullSink {
+            name: String::from("Recorder"),
+        };
+        assert_eq!(
+            null_sink.module(),
+            (
+                "module-null-sink",
+                String::from("sink_name=Recorder sink_properties=device.description=Recorder")
+            )
+        );
+
+        let loopback = Setup::Loopback {
+            source: SOURCE.to_owned(),
+            sink: SINK.to_owned(),
+        };
+        assert_eq!(
+            loopback.module(),
+            (
+                "module-loopback",
+                format!("source={} sink={}", SOURCE, SINK)
+            )
+        );
+
+        let combine_sink = Setup::CombineSink {
+            name: String::from("Both"),
+            sinks: vec![SINK.to_owned(), String::from("bluez_sink.headset")],
+        };
+        assert_eq!(
+            combine_sink.module(),
+            (
+                "module-combine-sink",
+                format!("sink_name=Both slaves={},bluez_sink.headset", SINK)
+            )
+        );
+
+        let echo_cancel = Setup::EchoCancel {
+            name: String::from("Clean"),
+            source: SOURCE.to_owned(),
+            sink: SINK.to_owned(),
+        };
+        assert_eq!(
+            echo_cancel.module(),
+            (
+                "module-echo-cancel",
+                format!(
+                    "source_name=Clean source_master={} sink_master={}",
+                    SOURCE, SINK
+                )
+            )
+        );
+    }
+
+    #[test]
+    fn accepts_device_names() {
+        let setup = Setup::EchoCancel {
+            name: String::from("echo-cancelled_1"),
+            source: SOURCE.to_owned(),
+            sink: SINK.to_owned(),
+        };
+
+        assert_eq!(setup.validate(), Ok(()));
+    }
+
+    #[test]
+    fn rejects_names_which_would_break_the_argument() {
+        for name in &["", "My sink", "sink_name=other", "a\"b", "tab\there"] {
+            let setup = Setup::NullSink {
+                name: name.to_string(),
+            };
+            assert_eq!(
+                setup.validate(),
+                Err(format!("Invalid device name \"{}\"", name))
+            );
+        }
+
+        let setup = Setup::CombineSink {
+            name: String::from("Both"),
+            sinks: vec![SINK.to_owned(), String::from("slaves=x")],
+        };
+        assert!(setup.validate().is_err());
+    }
+
+    #[test]
+    fn saves_the_setups_by_kind() {
+        let file = ModulesFile {
+            modules: vec![Tracked {
+                recreate: true,
+                index: Some(26),
+                setup: Setup::Loopback {
+                    source: SOURCE.to_owned(),
+                    sink: SINK.to_owned(),
+                },
+            }],
+        };
+
+        let content = toml::to_string(&file).unwrap();
+        assert!(content.contains("kind = \"loopback\""));
+
+        let loaded: ModulesFile = toml::from_str(&content).unwrap();
+        assert_eq!(loaded.modules[0].setup, file.modules[0].setup);
+        assert_eq!(loaded.modules[0].index, Some(26));
+        assert!(loaded.modules[0].recreate);
+    }
+}
@@ -0,0 +1,440 @@
+//! Media players of the session bus, through MPRIS.
+//!
+//! A player is matched to a sink input by the process id both report, then
+//! by comparing its desktop entry, identity and bus name with the name and
+//! binary of the stream. The players are read from the bus the
+//! `DBUS_SESSION_BUS_ADDRESS` variable points at, so a fake player on a
+//! private bus can stand in for real ones.
+//!
+//! Reading a player takes several calls, each of which may wait for a slow
+//! player, so the players are read on their own thread, which also sends
+//! the actions. The window only takes the latest reading.
+use std::sync::mpsc::{self, Receiver, RecvTimeoutError, Sender};
+use std::thread;
+use std::time::Duration;
+
+use dbus::arg::{PropMap, RefArg};
+use dbus::blocking::stdintf::org_freedesktop_dbus::Properties;
+use dbus::blocking::Connection;
+
+use crate::updater::SinkInputData;
+
+const BUS_PREFIX: &str = "org.mpris.MediaPlayer2.";
+const OBJECT_PATH: &str = "/org/mpris/MediaPlayer2";
+const ROOT_INTERFACE: &str = "org.mpris.MediaPlayer2";
+const PLAYER_INTERFACE: &str = "org.mpris.MediaPlayer2.Player";
+const DBUS_NAME: &str = "org.freedesktop.DBus";
+const DBUS_PATH: &str = "/org/freedesktop/DBus";
+/// Players answer quickly; a stuck one must not hold up the others.
+const TIMEOUT: Duration = Duration::from_millis(100);
+/// How often the players are read again.
+const READ_INTERVAL: Duration = Duration::from_secs(1);
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum PlaybackStatus {
+    Playing,
+    Paused,
+    Stopped,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Action {
+    Previous,
+    PlayPause,
+    Next,
+    Pause,
+}
+
+impl Action {
+    fn method(self) -> &'static str {
+        match self {
+            Action::Previous => "Previous",
+            Action::PlayPause => "PlayPause",
+            Action::Next => "Next",
+            Action::Pause => "Pause",
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct Player {
+    pub bus_name: String,
+    pub title: Option<String>,
+    pub artist: Option<String>,
+    pub status: PlaybackStatus,
+    pub can_go_previous: bool,
+    pub can_go_next: bool,
+    pid: Option<u32>,
+    /// Lowercase names to match the streams against.
+    names: Vec<String>,
+}
+
+impl Player {
+    /// The now-playing line, e.g. `Artist - Title`.
+    pub fn now_playing(&self) -> String {
+        match (&self.artist, &self.title) {
+            (Some(artist), Some(title)) => format!("{} - {}", artist, title),
+            (None, Some(title)) => title.clone(),
+            _ => String::new(),
+        }
+    }
+}
+
+/// An action for a player, sent from the reader thread.
+struct Command {
+    bus_name: String,
+    action: Action,
+}
+
+pub struct Mpris {
+    commands: Sender<Command>,
+    readings: Receiver<Vec<Player>>,
+    players: Vec<Player>,
+}
+
+impl Mpris {
+    /// Connects to the session bus, or returns `None` when there is none.
+    pub fn new() -> Option<Self> {
+        match Connection::new_session() {
+            Ok(connection) => Some(Self::with_connection(connection)),
+            Err(e) => {
+                log::warn!("Unable to connect to the session bus: {}", e);
+                None
+            }
+        }
+    }
+
+    /// Starts reading the players of the bus of `connection`.
+    fn with_connection(connection: Connection) -> Self {
+        let (commands, commands_receiver) = mpsc::channel();
+        let (readings_sender, readings) = mpsc::channel();
+        let reader = Reader { connection };
+        thread::spawn(move || reader.run(commands_receiver, readings_sender));
+
+        Self {
+            commands,
+            readings,
+            players: Vec::new(),
+        }
+    }
+
+    /// Takes the latest reading of the players, without waiting for one.
+    pub fn refresh(&mut self) {
+        if let Some(players) = self.readings.try_iter().last() {
+            self.players = players;
+        }
+    }
+
+    pub fn players(&self) -> &[Player] {
+        &self.players
+    }
+
+    /// The player of the stream, if one matches.
+    pub fn find(&self, input: &SinkInputData) -> Option<&Player> {
+        if let Some(pid) = input.pid {
+            if let Some(player) = self.players.iter().find(|player| player.pid == Some(pid)) {
+                return Some(player);
+            }
+        }
+
+        let stream_names: Vec<String> = std::iter::once(&input.name)
+            .chain(input.binary.iter())
+            .map(|name| name.to_lowercase())
+            .collect();
+        self.players
+            .iter()
+            .find(|player| player.names.iter().any(|name| stream_names.contains(name)))
+    }
+
+    /// Sends the action to the player; the players are read again after.
+    pub fn send(&self, bus_name: &str, action: Action) {
+        let command = Command {
+            bus_name: bus_name.to_owned(),
+            action,
+        };
+        if self.commands.send(command).is_err() {
+            log::error!(
+                "Unable to send {:?} to {}: the reader stopped",
+                action,
+                bus_name
+            );
+        }
+    }
+}
+
+/// The thread reading the players.
+struct Reader {
+    connection: Connection,
+}
+
+impl Reader {
+    /// Reads the players every `READ_INTERVAL` and after every action, until
+    /// the `Mpris` is dropped.
+    fn run(self, commands: Receiver<Command>, readings: Sender<Vec<Player>>) {
+        loop {
+            if let Some(players) = self.players() {
+                if readings.send(players).is_err() {
+                    return;
+                }
+            }
+
+            match commands.recv_timeout(READ_INTERVAL) {
+                Ok(command) => self.send(&command.bus_name, command.action),
+                Err(RecvTimeoutError::Timeout) => {}
+                Err(RecvTimeoutError::Disconnected) => return,
+            }
+        }
+    }
+
+    /// Reads the players and their metadata.
+    fn players(&self) -> Option<Vec<Player>> {
+        let bus = self.connection.with_proxy(DBUS_NAME, DBUS_PATH, TIMEOUT);
+        let names: Vec<String> = match bus.method_call(DBUS_NAME, "ListNames", ()) {
+            Ok((names,)) => names,
+            Err(e) => {
+                log::warn!("Unable to list the media players: {}", e);
+                return None;
+            }
+        };
+
+        Some(
+            names
+                .into_iter()
+                .filter(|name| name.starts_with(BUS_PREFIX))
+                .map(|name| self.read(name))
+                .collect(),
+        )
+    }
+
+    fn send(&self, bus_name: &str, action: Action) {
+        let player = self.connection.with_proxy(bus_name, OBJECT_PATH, TIMEOUT);
+        let result: Result<(), _> = player.method_call(PLAYER_INTERFACE, action.method(), ());
+
+        if let Err(e) = result {
+            log::error!("Unable to send {:?} to {}: {}", action, bus_name, e);
+        }
+    }
+
+    fn read(&self, bus_name: String) -> Player {
+        let bus = self.connection.with_proxy(DBUS_NAME, DBUS_PATH, TIMEOUT);
+        let pid = bus
+            .method_call(DBUS_NAME, "GetConnectionUnixProcessID", (&bus_name,))
+            .ok()
+            .map(|(pid,): (u32,)| pid);
+
+        let player = self.connection.with_proxy(&bus_name, OBJECT_PATH, TIMEOUT);
+        let metadata: PropMap = player.get(PLAYER_INTERFACE, "Metadata").unwrap_or_default();
+        let status: String = player
+            .get(PLAYER_INTERFACE, "PlaybackStatus")
+            .unwrap_or_default();
+
+        let mut names: Vec<String> = Vec::new();
+        let identity: Option<String> = player.get(ROOT_INTERFACE, "Identity").ok();
+        let desktop_entry: Option<String> = player.get(ROOT_INTERFACE, "DesktopEntry").ok();
+        names.extend(identity.into_iter().chain(desktop_entry));
+        // The bus name may carry an instance suffix, as in `firefox.instance42`.
+        names.extend(
+            bus_name[BUS_PREFIX.len()..]
+                .split('.')
+                .next()
+                .map(String::from),
+        );
+        for name in &mut names {
+            *name = name.to_lowercase();
+        }
+
+        Player {
+            title: metadata
+                .get("xesam:title")
+                .and_then(|title| title.0.as_str())
+                .map(String::from),
+            artist: metadata.get("xesam:artist").and_then(|artists| {
+                let artists: Vec<&str> = artists.0.as_iter()?.filter_map(|a| a.as_str()).collect();
+                Some(artists.join(", ")).filter(|artists| !artists.is_empty())
+            }),
+            status: match status.as_str() {
+                "Playing" => PlaybackStatus::Playing,
+                "Paused" => PlaybackStatus::Paused,
+                _ => PlaybackStatus::Stopped,
+            },
+            can_go_previous: player
+                .get(PLAYER_INTERFACE, "CanGoPrevious")
+                .unwrap_or(false),
+            can_go_next: player.get(PLAYER_INTERFACE, "CanGoNext").unwrap_or(false),
+            bus_name,
+            pid,
+            names,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::io::{BufRead, BufReader};
+    use std::process::{Child, Command as Process, Stdio};
+    use std::sync::atomic::{AtomicBool, Ordering};
+    use std::sync::Arc;
+    use std::thread::JoinHandle;
+    use std::time::Instant;
+
+    use dbus::arg::Variant;
+    use dbus::channel::{Channel, MatchingReceiver, Sender as _};
+    use dbus::message::MatchRule;
+    use dbus::MethodErr;
+
+    const PLAYER_NAME: &str = "org.mpris.MediaPlayer2.fakeplayer.instance7";
+    const WAIT: Duration = Duration::from_secs(5);
+
+    /// A `dbus-daemon` of the test's own, stopped with it.
+    struct Bus {
+        daemon: Child,
+        address: String,
+    }
+
+    impl Bus {
+        fn start() -> Option<Self> {
+            let mut daemon = Process::new("dbus-daemon")
+                .arg("--session")
+                .arg("--nofork")
+                .arg("--print-address")
+                .stdout(Stdio::piped())
+                .spawn()
+                .ok()?;
+
+            let mut address = String::new();
+            BufReader::new(daemon.stdout.as_mut()?)
+                .read_line(&mut address)
+                .ok()?;
+            Some(Self {
+                daemon,
+                address: address.trim().to_owned(),
+            })
+        }
+
+        fn connect(&self) -> Connection {
+            let mut channel = Channel::open_private(&self.address).unwrap();
+            channel.register().unwrap();
+            channel.into()
+        }
+    }
+
+    impl Drop for Bus {
+        fn drop(&mut self) {
+            let _ = self.daemon.kill();
+            let _ = self.daemon.wait();
+        }
+    }
+
+    fn property(name: &str) -> Option<Box<dyn RefArg>> {
+        let value: Box<dyn RefArg> = match name {
+            "Metadata" => {
+                let mut metadata = PropMap::new();
+                metadata.insert("xesam:title".into(), Variant(Box::new("Song".to_owned())));
+                metadata.insert(
+                    "xesam:artist".into(),
+                    Variant(Box::new(vec!["Artist".to_owned()])),
+                );
+                Box::new(metadata)
+            }
+            "PlaybackStatus" => Box::new("Playing".to_owned()),
+            "Identity" => Box::new("Fake Player".to_owned()),
+            "CanGoPrevious" => Box::new(false),
+            "CanGoNext" => Box::new(true),
+            _ => return None,
+        };
+        Some(value)
+    }
+
+    /// Serves a player on `connection` until `stop` is set, passing on the
+    /// methods called on it.
+    fn serve_player(
+        connection: Connection,
+        calls: Sender<String>,
+        stop: Arc<AtomicBool>,
+    ) -> JoinHandle<()> {
+        connection
+            .request_name(PLAYER_NAME, false, false, true)
+            .unwrap();
+        connection.start_receive(
+            MatchRule::new_method_call(),
+            Box::new(move |message, connection| {
+                let member = message.member().unwrap();
+                let reply = match &*member {
+                    "Get" => {
+                        let (_, name): (&str, &str) = message.read2().unwrap();
+                        match property(name) {
+                            Some(value) => message.method_return().append1(Variant(value)),
+                            None => MethodErr::no_property(&name).to_message(&message),
+                        }
+                    }
+                    _ => {
+                        calls.send(member.to_string()).unwrap();
+                        message.method_return()
+                    }
+                };
+                connection.send(reply).unwrap();
+                true
+            }),
+        );
+
+        thread::spawn(move || {
+            while !stop.load(Ordering::SeqCst) {
+                connection.process(Duration::from_millis(10)).unwrap();
+            }
+        })
+    }
+
+    fn stream(name: &str, binary: Option<&str>, pid: Option<u32>) -> SinkInputData {
+        SinkInputData {
+            name: name.to_owned(),
+            binary: binary.map(String::from),
+            pid,
+            ..SinkInputData::default()
+        }
+    }
+
+    #[test]
+    fn reads_and_controls_a_player_on_a_private_bus() {
+        let bus = match Bus::start() {
+            Some(bus) => bus,
+            None => {
+                eprintln!("dbus-daemon is not available, skipping");
+                return;
+            }
+        };
+        let (calls_sender, calls) = mpsc::channel();
+        let stop = Arc::new(AtomicBool::new(false));
+        let player = serve_player(bus.connect(), calls_sender, stop.clone());
+
+        let mut mpris = Mpris::with_connection(bus.connect());
+        let started_at = Instant::now();
+        while mpris.players.is_empty() && started_at.elapsed() < WAIT {
+            thread::sleep(Duration::from_millis(10));
+            mpris.refresh();
+        }
+
+        let found = mpris.find(&stream("Fake Player", None, None)).unwrap();
+        assert_eq!(found.bus_name, PLAYER_NAME);
+        assert_eq!(found.now_playing(), "Artist - Song");
+        assert_eq!(found.status, PlaybackStatus::Playing);
+        assert!(!found.can_go_previous);
+        assert!(found.can_go_next);
+
+        // By the bus name without its instance suffix, then by the process.
+        assert!(mpris
+            .find(&stream("mpv", Some("fakeplayer"), None))
+            .is_some());
+        assert!(mpris
+            .find(&stream("mpv", None, Some(std::process::id())))
+            .is_some());
+        assert!(mpris.find(&stream("mpv", Some("mpv"), None)).is_none());
+
+        mpris.send(PLAYER_NAME, Action::Next);
+        assert_eq!(calls.recv_timeout(WAIT).unwrap(), "Next");
+
+        stop.store(true, Ordering::SeqCst);
+        player.join().unwrap();
+    }
+}
@@ -0,0 +1,393 @@
+//! On-screen display of the volume of the default devices.
+//!
+//! `volume-controller-ui osd` watches the default sink and source of the
+//! server through a PulseAudio subscription, following the server when it
+//! switches them. Whenever the volume or the mute of one of them changes,
+//! e.g. from media keys, it starts `osd-show` for that device: a small
+//! borderless window showing the device description, a volume bar and the
+//! mute icon, which follows further changes and closes itself once they
+//! stop for the configured timeout.
+//!
+//! Nothing is shown while the mixer window is focused, as the change is
+//! already visible there.
+use std::cell::Cell;
+use std::fs;
+use std::path::PathBuf;
+use std::process::{self, Child};
+use std::rc::Rc;
+use std::thread;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+use iced::{
+    executor, time, window, Align, Application, Column, Command, Container, Element, Length,
+    ProgressBar, Row, Settings, Subscription, Text,
+};
+use pulse::context::subscribe::{Facility, InterestMaskSet, Operation as SubscriptionOperation};
+
+use crate::cli;
+use crate::config::{self, Config};
+use crate::theme::Theme;
+use crate::updater::{
+    fetch_server_info, fetch_sinks, fetch_sources, DeviceData, PulseHandler, ServerData,
+};
+
+const OSD_SIZE: (u32, u32) = (300, 90);
+const WATCH_INTERVAL: Duration = Duration::from_millis(50);
+const TICK_INTERVAL: Duration = Duration::from_millis(50);
+const MAX_VOLUME: u32 = 65536;
+const FOCUS_FILE: &str = "focused";
+const SINK_NAME: &str = "System Volume";
+const SOURCE_NAME: &str = "Microphone";
+const MUTED_ICON: &str = "\u{00D7}";
+const UNMUTED_ICON: &str = "\u{2669}";
+
+/// The `[osd]` section of the configuration file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct OsdConfig {
+    /// How long the display stays after the last change, in milliseconds.
+    pub timeout: u64,
+    /// The position of the top left corner of the display on the screen.
+    /// The window manager places it when unset.
+    pub position: Option<(i32, i32)>,
+}
+
+impl Default for OsdConfig {
+    fn default() -> Self {
+        Self {
+            timeout: 1500,
+            position: None,
+        }
+    }
+}
+
+/// The device an on-screen display is shown for.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Device {
+    Sink,
+    Source,
+}
+
+impl Device {
+    pub const ALL: [Device; 2] = [Device::Sink, Device::Source];
+
+    /// The command line name of the device.
+    pub fn arg(self) -> &'static str {
+        match self {
+            Device::Sink => "sink",
+            Device::Source => "source",
+        }
+    }
+
+    pub fn from_arg(arg: &str) -> Option<Self> {
+        Device::ALL.iter().copied().find(|device| device.arg() == arg)
+    }
+
+    /// The label shown when the server has no such default device.
+    fn name(self) -> &'static str {
+        match self {
+            Device::Sink => SINK_NAME,
+            Device::Source => SOURCE_NAME,
+        }
+    }
+
+    /// The name of the default device of the server.
+    fn default_name(self, server: &ServerData) -> &str {
+        match self {
+            Device::Sink => &server.default_sink,
+            Device::Source => &server.default_source,
+        }
+    }
+
+    /// Reads the device named `name`, if there is one.
+    fn fetch(self, handler: &mut PulseHandler, name: &str) -> Option<DeviceData> {
+        let devices = match self {
+            Device::Sink => fetch_sinks(handler),
+            Device::Source => fetch_sources(handler),
+        };
+        devices.into_iter().find(|device| device.name == name)
+    }
+
+    /// Reads the default device of the server.
+    fn fetch_default(self, handler: &mut PulseHandler) -> Option<DeviceData> {
+        let server = fetch_server_info(handler);
+        self.fetch(handler, self.default_name(&server))
+    }
+}
+
+/// The default device watched for one kind of device.
+struct Watched {
+    device: Device,
+    name: String,
+    /// The volume and mute last seen, `None` without a default device.
+    shown: Option<(u32, bool)>,
+}
+
+/// Watches the default devices and shows the display when they change.
+pub fn watch() {
+    let mut handler = match PulseHandler::new() {
+        Some(handler) => handler,
+        None => return,
+    };
+
+    let changed = Rc::new(Cell::new(false));
+    let changed_ref = changed.clone();
+    // Set at first, for the default devices to be read at the start.
+    let server_changed = Rc::new(Cell::new(true));
+    let server_changed_ref = server_changed.clone();
+    handler.subscribe(
+        InterestMaskSet::SINK | InterestMaskSet::SOURCE | InterestMaskSet::SERVER,
+        move |facility, operation, _index| match facility {
+            Some(Facility::Server) => server_changed_ref.set(true),
+            Some(Facility::Sink) | Some(Facility::Source)
+                if operation == Some(SubscriptionOperation::Changed) =>
+            {
+                changed_ref.set(true)
+            }
+            _ => {}
+        },
+    );
+
+    let mut watched: Vec<_> = Device::ALL
+        .iter()
+        .map(|&device| Watched {
+            device,
+            name: String::new(),
+            shown: None,
+        })
+        .collect();
+    let mut osd: Option<(Device, Child)> = None;
+
+    loop {
+        handler.poll();
+
+        // Switching the default device shows nothing, only the changes of
+        // the device switched to do.
+        if server_changed.replace(false) {
+            let server = fetch_server_info(&mut handler);
+            for watched in &mut watched {
+                let name = watched.device.default_name(&server);
+                if name != watched.name {
+                    watched.shown = watched
+                        .device
+                        .fetch(&mut handler, name)
+                        .map(|data| (data.volume, data.mute));
+                    watched.name = name.to_owned();
+                }
+            }
+        }
+
+        if changed.replace(false) {
+            for watched in &mut watched {
+                let current = watched
+                    .device
+                    .fetch(&mut handler, &watched.name)
+                    .map(|data| (data.volume, data.mute));
+                if current.is_none() || current == watched.shown {
+                    continue;
+                }
+                watched.shown = current;
+
+                if is_main_focused() {
+                    continue;
+                }
+                let device = watched.device;
+                osd = match osd.take() {
+                    // The running display follows the changes itself.
+                    Some((shown_device, mut child))
+                        if shown_device == device && is_running(&mut child) =>
+                    {
+                        Some((shown_device, child))
+                    }
+                    previous => {
+                        if let Some((_, mut child)) = previous {
+                            let _ = child.kill();
+                            let _ = child.wait();
+                        }
+                        cli::spawn_self(&["osd-show", device.arg()]).map(|child| (device, child))
+                    }
+                };
+            }
+        }
+
+        thread::sleep(WATCH_INTERVAL);
+    }
+}
+
+/// Opens the display of `device`.
+pub fn show(device: Device) {
+    let config = Config::load();
+
+    let mut settings = Settings::with_flags(Flags {
+        device,
+        timeout: Duration::from_millis(config.osd.timeout),
+        theme: Theme::from_config(&config.theme),
+    });
+    settings.window.size = OSD_SIZE;
+    settings.window.decorations = false;
+    settings.window.resizable = false;
+    settings.window.always_on_top = true;
+    if let Some((x, y)) = config.osd.position {
+        settings.window.position = window::Position::Specific(x, y);
+    }
+
+    Osd::run(settings);
+}
+
+/// Records whether the mixer window is focused, for the watcher to stay
+/// quiet meanwhile.
+pub fn set_main_focused(is_focused: bool) {
+    let path = match focus_file() {
+        Some(path) => path,
+        None => return,
+    };
+
+    let result = if is_focused {
+        path.parent()
+            .map_or(Ok(()), fs::create_dir_all)
+            .and_then(|_| fs::write(&path, process::id().to_string()))
+    } else {
+        match fs::remove_file(&path) {
+            Err(e) if e.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            result => result,
+        }
+    };
+
+    if let Err(e) = result {
+        log::error!("Unable to update {}: {}", path.display(), e);
+    }
+}
+
+/// Returns `true` when a running mixer window is focused.
+///
+/// The focus file of a mixer which was killed while focused is ignored.
+fn is_main_focused() -> bool {
+    focus_file()
+        .and_then(|path| fs::read_to_string(path).ok())
+        .and_then(|pid| pid.trim().parse::<u32>().ok())
+        .map_or(false, |pid| PathBuf::from(format!("/proc/{}", pid)).exists())
+}
+
+fn focus_file() -> Option<PathBuf> {
+    config::runtime_directory().map(|directory| directory.join(FOCUS_FILE))
+}
+
+fn is_running(child: &mut Child) -> bool {
+    match child.try_wait() {
+        Ok(None) => true,
+        _ => false,
+    }
+}
+
+struct Flags {
+    device: Device,
+    timeout: Duration,
+    theme: Theme,
+}
+
+struct Osd {
+    pulse_handler: PulseHandler,
+    device: Device,
+    /// The name of the default device the display was opened for.
+    name: String,
+    description: String,
+    shown: (u32, bool),
+    changed_at: Instant,
+    timeout: Duration,
+    theme: Theme,
+}
+
+#[derive(Debug, Clone)]
+enum Message {
+    Tick,
+}
+
+impl Application for Osd {
+    type Executor = executor::Default;
+    type Message = Message;
+    type Flags = Flags;
+
+    fn new(flags: Flags) -> (Self, Command<Message>) {
+        let mut pulse_handler = PulseHandler::new().unwrap();
+        let data = flags.device.fetch_default(&mut pulse_handler);
+        let (name, description, shown) = match data {
+            Some(data) => (data.name, data.description, (data.volume, data.mute)),
+            None => (String::new(), flags.device.name().into(), (0, false)),
+        };
+
+        (
+            Self {
+                pulse_handler,
+                device: flags.device,
+                name,
+                description,
+                shown,
+                changed_at: Instant::now(),
+                timeout: flags.timeout,
+                theme: flags.theme,
+            },
+            Command::none(),
+        )
+    }
+
+    fn title(&self) -> String {
+        self.description.clone()
+    }
+
+    fn update(&mut self, message: Message) -> Command<Message> {
+        match message {
+            Message::Tick => {
+                self.pulse_handler.poll();
+
+                if let Some(data) = self.device.fetch(&mut self.pulse_handler, &self.name) {
+                    if (data.volume, data.mute) != self.shown {
+                        self.shown = (data.volume, data.mute);
+                        self.changed_at = Instant::now();
+                    }
+                }
+
+                if self.changed_at.elapsed() >= self.timeout {
+                    process::exit(0);
+                }
+            }
+        }
+
+        Command::none()
+    }
+
+    fn subscription(&self) -> Subscription<Message> {
+        time::every(TICK_INTERVAL).map(|_| Message::Tick)
+    }
+
+    fn view(&mut self) -> Element<Message> {
+        let (volume, mute) = self.shown;
+        let percent = volume * 100 / MAX_VOLUME;
+        let icon = if mute { MUTED_ICON } else { UNMUTED_ICON };
+
+        let header = Row::new()
+            .spacing(10)
+            .align_items(Align::Center)
+            .push(Text::new(icon).size(24))
+            .push(Text::new(&self.description).size(20).width(Length::Fill))
+            .push(Text::new(format!("{}%", percent)).size(20));
+
+        let content = Column::new()
+            .spacing(10)
+            .push(header)
+            .push(
+                ProgressBar::new(0.0..=100.0, percent as f32)
+                    .height(Length::Units(8))
+                    .style(self.theme.progress_bar(mute)),
+            );
+
+        Container::new(content)
+            .width(Length::Fill)
+            .height(Length::Fill)
+            .padding(16)
+            .center_y()
+            .style(self.theme.container())
+            .into()
+    }
+}
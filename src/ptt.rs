@@ -0,0 +1,213 @@
+//! Push-to-talk and push-to-mute of the microphone.
+//!
+//! The microphone follows the triggers: a key held in the mixer window and
+//! the `ptt press`/`ptt release` requests of the command line or of the
+//! D-Bus service. Once every trigger is released, the change waits for the
+//! release delay, so that short pauses do not cut words.
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum PttMode {
+    /// The microphone is only muted by hand.
+    Off,
+    /// The microphone is muted unless a trigger is active.
+    PushToTalk,
+    /// The microphone is muted while a trigger is active.
+    PushToMute,
+}
+
+impl Default for PttMode {
+    fn default() -> Self {
+        PttMode::Off
+    }
+}
+
+/// The `[ptt]` section of the configuration file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct PttConfig {
+    pub mode: PttMode,
+    /// The key held in the window, named as iced names its key codes,
+    /// e.g. `Space`, `F12` or `LControl`.
+    pub key: Option<String>,
+    /// How long the microphone stays as it is after the last release, in
+    /// milliseconds.
+    pub release_delay: u64,
+}
+
+impl Default for PttConfig {
+    fn default() -> Self {
+        Self {
+            mode: PttMode::Off,
+            key: None,
+            release_delay: 200,
+        }
+    }
+}
+
+/// The state of the triggers.
+#[derive(Debug)]
+pub struct PushToTalk {
+    mode: PttMode,
+    key: Option<String>,
+    release_delay: Duration,
+    is_key_held: bool,
+    is_remote_held: bool,
+    released_at: Option<Instant>,
+}
+
+impl PushToTalk {
+    pub fn new(config: &PttConfig) -> Self {
+        Self {
+            mode: config.mode,
+            key: config.key.clone(),
+            release_delay: Duration::from_millis(config.release_delay),
+            is_key_held: false,
+            is_remote_held: false,
+            released_at: None,
+        }
+    }
+
+    pub fn mode(&self) -> PttMode {
+        self.mode
+    }
+
+    /// Returns `true` when `key` is the key of the window trigger.
+    pub fn is_key(&self, key: &str) -> bool {
+        self.key.as_deref() == Some(key)
+    }
+
+    pub fn set_key_held(&mut self, is_held: bool) {
+        let was_active = self.is_held();
+        self.is_key_held = is_held;
+        self.update(was_active);
+    }
+
+    pub fn set_remote_held(&mut self, is_held: bool) {
+        let was_active = self.is_held();
+        self.is_remote_held = is_held;
+        self.update(was_active);
+    }
+
+    /// The mute the microphone should have now, if the mode decides it.
+    pub fn mute(&self) -> Option<bool> {
+        let is_active = self.is_held()
+            || self.released_at.map_or(false, |released_at| {
+                released_at.elapsed() < self.release_delay
+            });
+
+        match self.mode {
+            PttMode::Off => None,
+            PttMode::PushToTalk => Some(!is_active),
+            PttMode::PushToMute => Some(is_active),
+        }
+    }
+
+    fn is_held(&self) -> bool {
+        self.is_key_held || self.is_remote_held
+    }
+
+    fn update(&mut self, was_active: bool) {
+        if self.is_held() {
+            self.released_at = None;
+        } else if was_active {
+            self.released_at = Some(Instant::now());
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn ptt(mode: PttMode, release_delay: u64) -> PushToTalk {
+        PushToTalk::new(&PttConfig {
+            mode,
+            key: Some(String::from("Space")),
+            release_delay,
+        })
+    }
+
+    /// Moves the last release back past the release delay.
+    fn wait_for_the_delay(ptt: &mut PushToTalk) {
+        let delay = ptt.release_delay + Duration::from_millis(1);
+        ptt.released_at = ptt.released_at.map(|released_at| released_at - delay);
+    }
+
+    #[test]
+    fn leaves_the_microphone_alone_when_off() {
+        let mut ptt = ptt(PttMode::Off, 0);
+        assert_eq!(ptt.mute(), None);
+
+        ptt.set_key_held(true);
+        assert_eq!(ptt.mute(), None);
+    }
+
+    #[test]
+    fn opens_the_microphone_while_pressed() {
+        let mut ptt = ptt(PttMode::PushToTalk, 0);
+        assert!(ptt.is_key("Space"));
+        assert!(!ptt.is_key("F12"));
+        assert_eq!(ptt.mute(), Some(true));
+
+        ptt.set_key_held(true);
+        assert_eq!(ptt.mute(), Some(false));
+
+        ptt.set_key_held(false);
+        assert_eq!(ptt.mute(), Some(true));
+    }
+
+    #[test]
+    fn mutes_the_microphone_while_pressed() {
+        let mut ptt = ptt(PttMode::PushToMute, 0);
+        assert_eq!(ptt.mute(), Some(false));
+
+        ptt.set_remote_held(true);
+        assert_eq!(ptt.mute(), Some(true));
+
+        ptt.set_remote_held(false);
+        assert_eq!(ptt.mute(), Some(false));
+    }
+
+    #[test]
+    fn waits_for_the_release_delay() {
+        let mut ptt = ptt(PttMode::PushToTalk, 200);
+
+        ptt.set_key_held(true);
+        ptt.set_key_held(false);
+        assert_eq!(ptt.mute(), Some(false));
+
+        wait_for_the_delay(&mut ptt);
+        assert_eq!(ptt.mute(), Some(true));
+    }
+
+    #[test]
+    fn stays_open_when_pressed_again_during_the_delay() {
+        let mut ptt = ptt(PttMode::PushToTalk, 200);
+
+        ptt.set_key_held(true);
+        ptt.set_key_held(false);
+        ptt.set_key_held(true);
+        wait_for_the_delay(&mut ptt);
+        assert_eq!(ptt.mute(), Some(false));
+    }
+
+    #[test]
+    fn releases_once_the_key_and_the_remote_are_both_released() {
+        let mut ptt = ptt(PttMode::PushToTalk, 200);
+
+        ptt.set_key_held(true);
+        ptt.set_remote_held(true);
+        ptt.set_key_held(false);
+        wait_for_the_delay(&mut ptt);
+        assert_eq!(ptt.mute(), Some(false));
+
+        ptt.set_remote_held(false);
+        assert_eq!(ptt.mute(), Some(false));
+        wait_for_the_delay(&mut ptt);
+        assert_eq!(ptt.mute(), Some(true));
+    }
+}
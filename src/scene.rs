@@ -0,0 +1,449 @@
+//! Named mixer setups, saved in the `scenes` directory of the
+//! configuration directory as `<name>.toml`.
+//!
+//! A scene holds the default devices, the levels of the devices and the
+//! levels of the applications. Devices and applications are matched by
+//! their name, so a scene keeps working across restarts of the streams and
+//! replugs of the devices; the ones which are missing when the scene is
+//! applied are skipped.
+use std::collections::BTreeMap;
+use std::fs;
+use std::io::ErrorKind;
+use std::path::PathBuf;
+use std::time::Duration;
+
+use serde::{Deserialize, Serialize};
+
+use crate::config::{self, Config};
+use crate::fade::{self, FadeConfig, Fader};
+use crate::updater::{
+    fetch_server_info, fetch_sink_inputs, fetch_sinks, fetch_sources, update_default_sink,
+    update_default_source, update_sink_input_mute_by_id, update_sink_level_by_index,
+    update_source_level_by_index, DeviceData, PulseHandler, Target,
+};
+
+const SCENE_DIRECTORY: &str = "scenes";
+const SCENE_EXTENSION: &str = "toml";
+const MAX_VOLUME: u32 = 65536;
+
+/// The volume and mute of a device or an application.
+#[derive(Debug, Clone, Copy, PartialEq, Serialize, Deserialize)]
+pub struct Level {
+    pub volume: u32,
+    pub mute: bool,
+}
+
+impl From<&DeviceData> for Level {
+    fn from(data: &DeviceData) -> Self {
+        Self {
+            volume: data.volume,
+            mute: data.mute,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Scene {
+    pub default_sink: Option<String>,
+    pub default_source: Option<String>,
+    /// The levels of the sinks, by device name.
+    pub sinks: BTreeMap<String, Level>,
+    /// The levels of the sources, by device name.
+    pub sources: BTreeMap<String, Level>,
+    /// The levels of the applications, by name.
+    pub applications: BTreeMap<String, Level>,
+}
+
+impl Scene {
+    /// Captures the current setup.
+    pub fn capture(handler: &mut PulseHandler) -> Self {
+        let server = fetch_server_info(handler);
+        let levels = |devices: Vec<DeviceData>| {
+            devices
+                .iter()
+                .map(|device| (device.name.clone(), Level::from(device)))
+                .collect()
+        };
+
+        let mut applications = BTreeMap::new();
+        for input in fetch_sink_inputs(handler) {
+            applications.entry(input.name).or_insert(Level {
+                volume: input.volume,
+                mute: input.mute,
+            });
+        }
+
+        Self {
+            default_sink: Some(server.default_sink),
+            default_source: Some(server.default_source),
+            sinks: levels(fetch_sinks(handler)),
+            sources: levels(fetch_sources(handler)),
+            applications,
+        }
+    }
+
+    /// Applies the scene, returning why the devices and applications which
+    /// are missing were skipped, as `<name>: <reason>`.
+    ///
+    /// The levels of the devices are set at once. The mutes of the
+    /// applications and the default devices are only sent; they complete on
+    /// the next polls of the handler. The volumes of the applications are
+    /// faded by `fader`.
+    pub fn apply(
+        &self,
+        handler: &mut PulseHandler,
+        fader: &mut Fader,
+        config: &FadeConfig,
+    ) -> Vec<String> {
+        let duration = Duration::from_millis(config.scene);
+        let mut fade = |target, from, to| {
+            if from != to {
+                fader.start(target, from, to, duration, config.curve);
+            }
+        };
+
+        if let Some(name) = &self.default_sink {
+            update_default_sink(handler, name);
+        }
+        if let Some(name) = &self.default_source {
+            update_default_source(handler, name);
+        }
+
+        let mut skipped = Vec::new();
+        let sinks = fetch_sinks(handler);
+        for (name, level) in &self.sinks {
+            match sinks.iter().find(|sink| &sink.name == name) {
+                Some(sink) if Level::from(sink) != *level => {
+                    update_sink_level_by_index(handler, sink.index, level.volume, level.mute)
+                }
+                Some(_) => {}
+                None => skipped.push(format!("{}: not plugged in", name)),
+            }
+        }
+        let sources = fetch_sources(handler);
+        for (name, level) in &self.sources {
+            match sources.iter().find(|source| &source.name == name) {
+                Some(source) if Level::from(source) != *level => {
+                    update_source_level_by_index(handler, source.index, level.volume, level.mute)
+                }
+                Some(_) => {}
+                None => skipped.push(format!("{}: not plugged in", name)),
+            }
+        }
+
+        let inputs = fetch_sink_inputs(handler);
+        for (name, level) in &self.applications {
+            let mut is_running = false;
+
+            for input in inputs.iter().filter(|input| &input.name == name) {
+                is_running = true;
+                fade(Target::SinkInput(input.id), input.volume, level.volume);
+                if level.mute != input.mute {
+                    update_sink_input_mute_by_id(handler, input.id, level.mute, input.mute);
+                }
+            }
+
+            if !is_running {
+                skipped.push(format!("{}: not running", name));
+            }
+        }
+
+        skipped
+    }
+
+    /// Describes what applying the scene would change from `current`.
+    pub fn diff(&self, current: &Scene) -> Vec<String> {
+        let mut differences = Vec::new();
+
+        let mut compare_device =
+            |label: &str, wanted: &Option<String>, current: &Option<String>| {
+                if let (Some(wanted), Some(current)) = (wanted, current) {
+                    if wanted != current {
+                        differences.push(format!("{}: {} -> {}", label, current, wanted));
+                    }
+                }
+            };
+        compare_device("Default sink", &self.default_sink, &current.default_sink);
+        compare_device(
+            "Default source",
+            &self.default_source,
+            &current.default_source,
+        );
+
+        let levels = vec![
+            (&self.sinks, &current.sinks, "not plugged in"),
+            (&self.sources, &current.sources, "not plugged in"),
+            (&self.applications, &current.applications, "not running"),
+        ];
+        for (wanted, current, absent) in levels {
+            for (name, wanted) in wanted {
+                match current.get(name) {
+                    Some(current) => differences.extend(compare_levels(name, *wanted, *current)),
+                    None => differences.push(format!("{}: {}", name, absent)),
+                }
+            }
+        }
+
+        differences
+    }
+}
+
+/// A `scene` command of the command line.
+#[derive(Debug, Clone, PartialEq)]
+pub enum Command {
+    List,
+    Save(String),
+    Apply(String),
+    Diff(String),
+}
+
+/// Runs a command of the command line, printing its outcome.
+pub fn run(command: Command) -> Result<(), String> {
+    if command == Command::List {
+        for name in list() {
+            println!("{}", name);
+        }
+        return Ok(());
+    }
+
+    let mut handler = PulseHandler::new().ok_or("Unable to connect to the server")?;
+
+    match command {
+        Command::List => {}
+        Command::Save(name) => save(&name, &Scene::capture(&mut handler))?,
+        Command::Apply(name) => {
+            let mut fader = Fader::default();
+            let skipped = load(&name)?.apply(&mut handler, &mut fader, &Config::load().fade);
+            fade::finish(&mut handler, &mut fader);
+            for skipped in skipped {
+                println!("Skipped {}", skipped);
+            }
+        }
+        Command::Diff(name) => {
+            for difference in load(&name)?.diff(&Scene::capture(&mut handler)) {
+                println!("{}", difference);
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Lists the saved scenes, by name.
+pub fn list() -> Vec<String> {
+    let entries = match directory().map(fs::read_dir) {
+        Some(Ok(entries)) => entries,
+        _ => return Vec::new(),
+    };
+
+    let mut names: Vec<String> = entries
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.path())
+        .filter(|path| path.extension().map_or(false, |ext| ext == SCENE_EXTENSION))
+        .filter_map(|path| Some(path.file_stem()?.to_str()?.to_owned()))
+        .collect();
+    names.sort();
+    names
+}
+
+pub fn load(name: &str) -> Result<Scene, String> {
+    let path = path(name)?;
+
+    match fs::read_to_string(&path) {
+        Ok(content) => {
+            toml::from_str(&content).map_err(|e| format!("Invalid scene {}: {}", path.display(), e))
+        }
+        Err(e) if e.kind() == ErrorKind::NotFound => Err(format!("No scene named \"{}\"", name)),
+        Err(e) => Err(format!("Unable to read {}: {}", path.display(), e)),
+    }
+}
+
+pub fn save(name: &str, scene: &Scene) -> Result<(), String> {
+    let path = path(name)?;
+    let content = toml::to_string(scene).map_err(|e| format!("Unable to save the scene: {}", e))?;
+
+    path.parent()
+        .map_or(Ok(()), fs::create_dir_all)
+        .and_then(|_| fs::write(&path, content))
+        .map_err(|e| format!("Unable to write {}: {}", path.display(), e))
+}
+
+fn directory() -> Option<PathBuf> {
+    config::directory().map(|directory| directory.join(SCENE_DIRECTORY))
+}
+
+fn path(name: &str) -> Result<PathBuf, String> {
+    let is_valid =
+        !name.is_empty() && !name.starts_with('.') && !name.contains(|c| c == '/' || c == '\0');
+    if !is_valid {
+        return Err(format!("Invalid scene name \"{}\"", name));
+    }
+
+    directory()
+        .map(|directory| directory.join(format!("{}.{}", name, SCENE_EXTENSION)))
+        .ok_or_else(|| "No configuration directory".to_owned())
+}
+
+fn compare_levels(label: &str, wanted: Level, current: Level) -> Vec<String> {
+    let mut differences = Vec::new();
+
+    if wanted.volume != current.volume {
+        differences.push(format!(
+            "{}: {}% -> {}%",
+            label,
+            current.volume * 100 / MAX_VOLUME,
+            wanted.volume * 100 / MAX_VOLUME
+        ));
+    }
+    if wanted.mute != current.mute {
+        let state = |mute| if mute { "muted" } else { "unmuted" };
+        differences.push(format!(
+            "{}: {} -> {}",
+            label,
+            state(current.mute),
+            state(wanted.mute)
+        ));
+    }
+
+    differences
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const HEADSET: &str = "alsa_output.usb-headset.analog-stereo";
+    const SPEAKERS: &str = "alsa_output.pci.analog-stereo";
+
+    fn level(volume: u32, mute: bool) -> Level {
+        Level { volume, mute }
+    }
+
+    fn scene() -> Scene {
+        toml::from_str(&format!(
+            r#"
+            default_sink = "{headset}"
+            default_source = "alsa_input.pci.analog-stereo"
+
+            [sinks."{headset}"]
+            volume = 32768
+            mute = false
+
+            [sinks."{speakers}"]
+            volume = 65536
+            mute = true
+
+            [applications.Firefox]
+            volume = 65536
+            mute = false
+            "#,
+            headset = HEADSET,
+            speakers = SPEAKERS,
+        ))
+        .unwrap()
+    }
+
+    #[test]
+    fn keeps_the_levels_of_the_devices_by_name() {
+        let scene = scene();
+
+        assert_eq!(scene.sinks.get(HEADSET), Some(&level(32768, false)));
+        assert_eq!(scene.sinks.get(SPEAKERS), Some(&level(65536, true)));
+        assert!(scene.sources.is_empty());
+        assert_eq!(
+            toml::from_str::<Scene>(&toml::to_string(&scene).unwrap()).unwrap(),
+            scene
+        );
+    }
+
+    #[test]
+    fn describes_no_differences_from_itself() {
+        let scene = scene();
+
+        assert!(scene.diff(&scene).is_empty());
+    }
+
+    #[test]
+    fn describes_the_differences_of_each_device() {
+        let wanted = scene();
+        let mut current = scene();
+        current.default_sink = Some(SPEAKERS.to_owned());
+        current
+            .sinks
+            .insert(HEADSET.to_owned(), level(65536, false));
+        current
+            .sinks
+            .insert(SPEAKERS.to_owned(), level(65536, false));
+
+        assert_eq!(
+            wanted.diff(&current),
+            vec![
+                format!("Default sink: {} -> {}", SPEAKERS, HEADSET),
+                format!("{}: unmuted -> muted", SPEAKERS),
+                format!("{}: 100% -> 50%", HEADSET),
+            ]
+        );
+    }
+
+    #[test]
+    fn describes_the_missing_devices_and_applications() {
+        let wanted = scene();
+        let mut current = scene();
+        current.sinks.remove(SPEAKERS);
+        current.applications.clear();
+
+        assert_eq!(
+            wanted.diff(&current),
+            vec![
+                format!("{}: not plugged in", SPEAKERS),
+                "Firefox: not running".to_owned(),
+            ]
+        );
+    }
+
+    #[test]
+    fn ignores_the_default_devices_it_does_not_set() {
+        let mut wanted = scene();
+        wanted.default_sink = None;
+        let mut current = scene();
+        current.default_sink = Some(SPEAKERS.to_owned());
+
+        assert!(wanted.diff(&current).is_empty());
+    }
+
+    #[test]
+    fn compares_the_volumes_in_percent_and_the_mutes() {
+        assert_eq!(
+            compare_levels("mpv", level(16384, true), level(49152, false)),
+            vec!["mpv: 75% -> 25%", "mpv: unmuted -> muted"]
+        );
+        assert_eq!(
+            compare_levels("mpv", level(49152, false), level(49152, true)),
+            vec!["mpv: muted -> unmuted"]
+        );
+        assert!(compare_levels("mpv", level(49152, false), level(49152, false)).is_empty());
+    }
+
+    #[test]
+    fn rejects_names_outside_of_the_scene_directory() {
+        for name in &["", ".hidden", "..", "../config", "a/b", "a\0b"] {
+            assert_eq!(
+                path(name),
+                Err(format!("Invalid scene name \"{}\"", name)),
+                "{:?}",
+                name
+            );
+        }
+    }
+
+    #[test]
+    fn saves_the_scenes_by_name() {
+        for name in &["Evening", "Late night", "Zoom call.v2"] {
+            if let Ok(path) = path(name) {
+                assert_eq!(path.file_name(), Some(format!("{}.toml", name).as_ref()));
+                assert!(path.parent().unwrap().ends_with(SCENE_DIRECTORY));
+            }
+        }
+    }
+}
@@ -0,0 +1,439 @@
+//! D-Bus control interface of the running mixer.
+//!
+//! The service owns `io.github.KrutNA.VolumeController` on the session bus
+//! given by `DBUS_SESSION_BUS_ADDRESS`, so it can be tried against a
+//! private `dbus-daemon`. It runs on its own thread with its own
+//! PulseAudio context, and mirrors the `updater` API:
+//!
+//! ```text
+//! ListStreams() -> a(susub)            kind, id, name, volume, mute
+//! ListSinks() -> a(uss)                index, name, description
+//! ListSources() -> a(uss)
+//! SetVolume(s kind, u id, u volume)
+//! SetMute(s kind, u id, b mute)
+//! MoveStream(u id, u sink)
+//! SetDefaultSink(s name)
+//! SetDefaultSource(s name)
+//! PushToTalk(b pressed)
+//! ListScenes() -> as
+//! SaveScene(s name)
+//! ApplyScene(s name) -> as             the devices and applications skipped
+//! DiffScene(s name) -> as              the changes applying would make
+//!
+//! signal Changed(s kind, u id, s name, u volume, b mute)
+//! signal Removed(s kind, u id)
+//! ```
+//!
+//! A stream `kind` is `sink-input`, `sink` or `source`; the `id` is only
+//! meaningful for sink inputs. Volumes go from 0 to 65536 (100%).
+use std::cell::{Cell, RefCell};
+use std::rc::Rc;
+use std::sync::mpsc::Sender;
+use std::thread;
+use std::time::Duration;
+
+use dbus::blocking::stdintf::org_freedesktop_dbus::RequestNameReply;
+use dbus::blocking::LocalConnection;
+use dbus::channel::{MatchingReceiver, Sender as _};
+use dbus::message::MatchRule;
+use dbus::{Message, MethodErr};
+use pulse::context::subscribe::InterestMaskSet;
+
+use crate::config::Config;
+use crate::fade::{self, Fader};
+use crate::instance::Request;
+use crate::limit::LimitConfig;
+use crate::scene::{self, Scene};
+use crate::updater::{
+    fetch_sink_inputs, fetch_sinks, fetch_sources, move_sink_input_by_id, update_default_sink,
+    update_default_source, update_fetch_sink, update_fetch_source, update_sink_input_mute_by_id,
+    update_sink_input_volume_by_id, update_sink_mute, update_sink_volume, update_source_mute,
+    update_source_volume, DeviceData, MainData, PulseHandler, Target,
+};
+
+const BUS_NAME: &str = "io.github.KrutNA.VolumeController";
+const OBJECT_PATH: &str = "/io/github/KrutNA/VolumeController";
+const INTERFACE: &str = "io.github.KrutNA.VolumeController";
+const INTROSPECTABLE: &str = "org.freedesktop.DBus.Introspectable";
+const PROCESS_TIMEOUT: Duration = Duration::from_millis(50);
+
+const SINK_INPUT: &str = "sink-input";
+const SINK: &str = "sink";
+const SOURCE: &str = "source";
+const SINK_NAME: &str = "System Volume";
+const SOURCE_NAME: &str = "Microphone";
+
+const INTROSPECTION: &str = r#"<!DOCTYPE node PUBLIC "-//freedesktop//DTD D-BUS Object Introspection 1.0//EN"
+ "http://www.freedesktop.org/standards/dbus/1.0/introspect.dtd">
+<node>
+  <interface name="org.freedesktop.DBus.Introspectable">
+    <method name="Introspect">
+      <arg name="data" type="s" direction="out"/>
+    </method>
+  </interface>
+  <interface name="io.github.KrutNA.VolumeController">
+    <method name="ListStreams">
+      <arg name="streams" type="a(susub)" direction="out"/>
+    </method>
+    <method name="ListSinks">
+      <arg name="sinks" type="a(uss)" direction="out"/>
+    </method>
+    <method name="ListSources">
+      <arg name="sources" type="a(uss)" direction="out"/>
+    </method>
+    <method name="SetVolume">
+      <arg name="kind" type="s" direction="in"/>
+      <arg name="id" type="u" direction="in"/>
+      <arg name="volume" type="u" direction="in"/>
+    </method>
+    <method name="SetMute">
+      <arg name="kind" type="s" direction="in"/>
+      <arg name="id" type="u" direction="in"/>
+      <arg name="mute" type="b" direction="in"/>
+    </method>
+    <method name="MoveStream">
+      <arg name="id" type="u" direction="in"/>
+      <arg name="sink" type="u" direction="in"/>
+    </method>
+    <method name="SetDefaultSink">
+      <arg name="name" type="s" direction="in"/>
+    </method>
+    <method name="SetDefaultSource">
+      <arg name="name" type="s" direction="in"/>
+    </method>
+    <method name="PushToTalk">
+      <arg name="pressed" type="b" direction="in"/>
+    </method>
+    <method name="ListScenes">
+      <arg name="scenes" type="as" direction="out"/>
+    </method>
+    <method name="SaveScene">
+      <arg name="name" type="s" direction="in"/>
+    </method>
+    <method name="ApplyScene">
+      <arg name="name" type="s" direction="in"/>
+      <arg name="skipped" type="as" direction="out"/>
+    </method>
+    <method name="DiffScene">
+      <arg name="name" type="s" direction="in"/>
+      <arg name="differences" type="as" direction="out"/>
+    </method>
+    <signal name="Changed">
+      <arg name="kind" type="s"/>
+      <arg name="id" type="u"/>
+      <arg name="name" type="s"/>
+      <arg name="volume" type="u"/>
+      <arg name="mute" type="b"/>
+    </signal>
+    <signal name="Removed">
+      <arg name="kind" type="s"/>
+      <arg name="id" type="u"/>
+    </signal>
+  </interface>
+</node>"#;
+
+/// A stream as sent over the bus: kind, id, name, volume and mute.
+type Stream = (String, u32, String, u32, bool);
+
+/// Starts the service on its own thread.
+///
+/// The calls which concern the window itself are passed on as `requests`.
+pub fn spawn(requests: Sender<Request>) {
+    thread::spawn(move || {
+        if let Err(e) = run(requests) {
+            log::error!("D-Bus service stopped: {}", e);
+        }
+    });
+}
+
+fn run(requests: Sender<Request>) -> Result<(), dbus::Error> {
+    let handler = match PulseHandler::new() {
+        Some(handler) => Rc::new(RefCell::new(handler)),
+        None => return Ok(()),
+    };
+
+    let connection = LocalConnection::new_session()?;
+    if connection.request_name(BUS_NAME, false, false, true)? != RequestNameReply::PrimaryOwner {
+        log::warn!(
+            "{} is already owned, not starting the D-Bus service",
+            BUS_NAME
+        );
+        return Ok(());
+    }
+
+    let fader = Rc::new(RefCell::new(Fader::default()));
+    let handler_ref = handler.clone();
+    let fader_ref = fader.clone();
+    connection.start_receive(
+        MatchRule::new_method_call(),
+        Box::new(move |message, connection| {
+            let reply = handle(
+                &mut handler_ref.borrow_mut(),
+                &mut fader_ref.borrow_mut(),
+                &requests,
+                &message,
+            )
+            .unwrap_or_else(|e| e.to_message(&message));
+            if connection.send(reply).is_err() {
+                log::error!("Unable to reply to a D-Bus call");
+            }
+            true
+        }),
+    );
+
+    let changed = Rc::new(Cell::new(false));
+    let changed_ref = changed.clone();
+    handler.borrow_mut().subscribe(
+        InterestMaskSet::SINK_INPUT | InterestMaskSet::SINK | InterestMaskSet::SOURCE,
+        move |_facility, operation, _index| {
+            if operation.is_some() {
+                changed_ref.set(true);
+            }
+        },
+    );
+
+    let mut streams = list_streams(&mut handler.borrow_mut());
+
+    loop {
+        connection.process(PROCESS_TIMEOUT)?;
+        for step in fader.borrow_mut().step() {
+            fade::send(&mut handler.borrow_mut(), step);
+        }
+        handler.borrow_mut().poll();
+
+        if !changed.replace(false) {
+            continue;
+        }
+
+        let current = list_streams(&mut handler.borrow_mut());
+        for signal in diff(&streams, &current) {
+            if connection.send(signal).is_err() {
+                log::error!("Unable to send a D-Bus signal");
+            }
+        }
+        streams = current;
+    }
+}
+
+fn handle(
+    handler: &mut PulseHandler,
+    fader: &mut Fader,
+    requests: &Sender<Request>,
+    message: &Message,
+) -> Result<Message, MethodErr> {
+    let member = message.member().ok_or_else(|| MethodErr::no_method(&""))?;
+    let interface = message.interface();
+
+    match (interface.as_deref(), &*member) {
+        (Some(INTROSPECTABLE), "Introspect") => {
+            return Ok(message.method_return().append1(INTROSPECTION));
+        }
+        (Some(INTERFACE), _) | (None, _) => {}
+        (Some(interface), _) => return Err(MethodErr::no_interface(&interface)),
+    }
+
+    match &*member {
+        "ListStreams" => {
+            return Ok(message.method_return().append1(list_streams(handler)));
+        }
+        "ListSinks" => {
+            return Ok(message
+                .method_return()
+                .append1(devices(fetch_sinks(handler))));
+        }
+        "ListSources" => {
+            return Ok(message
+                .method_return()
+                .append1(devices(fetch_sources(handler))));
+        }
+        "SetVolume" => {
+            let (kind, id, volume): (String, u32, u32) = message.read3()?;
+            let stream = find(handler, &kind, id)?;
+            let previous = stream.3;
+            let volume = match cap(handler, &Config::load().limits, &stream) {
+                Some(cap) => volume.min(cap),
+                None => volume,
+            };
+            fader.cancel(target(&kind, id));
+
+            match kind.as_str() {
+                SINK_INPUT => update_sink_input_volume_by_id(handler, id, volume, previous),
+                SINK => update_sink_volume(handler, volume, previous),
+                _ => update_source_volume(handler, volume, previous),
+            }
+        }
+        "SetMute" => {
+            let (kind, id, mute): (String, u32, bool) = message.read3()?;
+            let previous = find(handler, &kind, id)?.4;
+            fader.cancel(target(&kind, id));
+
+            match kind.as_str() {
+                SINK_INPUT => update_sink_input_mute_by_id(handler, id, mute, previous),
+                SINK => update_sink_mute(handler, mute, previous),
+                _ => update_source_mute(handler, mute, previous),
+            }
+        }
+        "MoveStream" => {
+            let (id, sink): (u32, u32) = message.read2()?;
+            find(handler, SINK_INPUT, id)?;
+            if !fetch_sinks(handler)
+                .iter()
+                .any(|device| device.index == sink)
+            {
+                return Err(MethodErr::invalid_arg(&sink));
+            }
+
+            move_sink_input_by_id(handler, id, sink);
+        }
+        "SetDefaultSink" => {
+            let name: String = message.read1()?;
+            if !fetch_sinks(handler)
+                .iter()
+                .any(|device| device.name == name)
+            {
+                return Err(MethodErr::invalid_arg(&name));
+            }
+
+            update_default_sink(handler, &name);
+        }
+        "SetDefaultSource" => {
+            let name: String = message.read1()?;
+            if !fetch_sources(handler)
+                .iter()
+                .any(|device| device.name == name)
+            {
+                return Err(MethodErr::invalid_arg(&name));
+            }
+
+            update_default_source(handler, &name);
+        }
+        "PushToTalk" => {
+            let pressed: bool = message.read1()?;
+            requests
+                .send(Request::Talk(pressed))
+                .map_err(|_| MethodErr::failed(&"The mixer window is closed"))?;
+        }
+        "ListScenes" => {
+            return Ok(message.method_return().append1(scene::list()));
+        }
+        "SaveScene" => {
+            let name: String = message.read1()?;
+            scene::save(&name, &Scene::capture(handler)).map_err(|e| MethodErr::failed(&e))?;
+        }
+        "ApplyScene" => {
+            let name: String = message.read1()?;
+            let skipped = scene::load(&name)
+                .map_err(|e| MethodErr::failed(&e))?
+                .apply(handler, fader, &Config::load().fade);
+
+            return Ok(message.method_return().append1(skipped));
+        }
+        "DiffScene" => {
+            let name: String = message.read1()?;
+            let differences = scene::load(&name)
+                .map_err(|e| MethodErr::failed(&e))?
+                .diff(&Scene::capture(handler));
+
+            return Ok(message.method_return().append1(differences));
+        }
+        member => return Err(MethodErr::no_method(&member)),
+    }
+
+    Ok(message.method_return())
+}
+
+/// Looks up the stream a call refers to.
+fn find(handler: &mut PulseHandler, kind: &str, id: u32) -> Result<Stream, MethodErr> {
+    if ![SINK_INPUT, SINK, SOURCE].contains(&kind) {
+        return Err(MethodErr::invalid_arg(&kind));
+    }
+
+    list_streams(handler)
+        .into_iter()
+        .find(|stream| stream.0 == kind && (kind != SINK_INPUT || stream.1 == id))
+        .ok_or_else(|| MethodErr::invalid_arg(&id))
+}
+
+/// The highest volume of a stream found by `find`, if it is capped.
+fn cap(handler: &mut PulseHandler, limits: &LimitConfig, stream: &Stream) -> Option<u32> {
+    match stream.0.as_str() {
+        SINK_INPUT => limits.application(&stream.2),
+        // The sink is listed under a display name, so its own is looked up.
+        SINK => fetch_sinks(handler)
+            .into_iter()
+            .find(|device| device.index == 0)
+            .and_then(|device| limits.sink(&device.name)),
+        _ => None,
+    }
+}
+
+/// The target of a stream already checked by `find`.
+fn target(kind: &str, id: u32) -> Target {
+    match kind {
+        SINK_INPUT => Target::SinkInput(id),
+        SINK => Target::Sink,
+        _ => Target::Source,
+    }
+}
+
+fn list_streams(handler: &mut PulseHandler) -> Vec<Stream> {
+    let sink = Rc::new(RefCell::new(MainData::default()));
+    let source = Rc::new(RefCell::new(MainData::default()));
+    update_fetch_sink(handler, sink.clone());
+    update_fetch_source(handler, source.clone());
+
+    let sink = sink.borrow();
+    let source = source.borrow();
+
+    let mut streams = vec![
+        (SINK.into(), 0, SINK_NAME.into(), sink.volume, sink.mute),
+        (
+            SOURCE.into(),
+            0,
+            SOURCE_NAME.into(),
+            source.volume,
+            source.mute,
+        ),
+    ];
+    streams.extend(fetch_sink_inputs(handler).into_iter().map(|input| {
+        (
+            SINK_INPUT.into(),
+            input.id,
+            input.name,
+            input.volume,
+            input.mute,
+        )
+    }));
+    streams
+}
+
+fn devices(devices: Vec<DeviceData>) -> Vec<(u32, String, String)> {
+    devices
+        .into_iter()
+        .map(|device| (device.index, device.name, device.description))
+        .collect()
+}
+
+/// The signals telling how `old` became `new`.
+fn diff(old: &[Stream], new: &[Stream]) -> Vec<Message> {
+    let same_stream = |a: &Stream, b: &Stream| a.0 == b.0 && a.1 == b.1;
+
+    let removed = old
+        .iter()
+        .filter(|stream| !new.iter().any(|other| same_stream(stream, other)))
+        .map(|(kind, id, ..)| signal("Removed").append2(kind.as_str(), *id));
+    let changed =
+        new.iter()
+            .filter(|stream| !old.contains(stream))
+            .map(|(kind, id, name, volume, mute)| {
+                signal("Changed")
+                    .append3(kind.as_str(), *id, name.as_str())
+                    .append2(*volume, *mute)
+            });
+
+    removed.chain(changed).collect()
+}
+
+fn signal(name: &str) -> Message {
+    Message::new_signal(OBJECT_PATH, INTERFACE, name).expect("Invalid D-Bus signal")
+}
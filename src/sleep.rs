@@ -0,0 +1,195 @@
+//! Sleep timer of the mixer window.
+//!
+//! Once the timer runs out, the default sink or the chosen applications are
+//! faded out and muted, and their media players may be paused once the fade
+//! is over. The delay is given in minutes or as a wall-clock time, which is
+//! the next one to come.
+use std::mem;
+use std::ptr;
+use std::time::{Duration, Instant};
+
+use serde::Deserialize;
+
+const SECONDS_PER_DAY: i64 = 24 * 60 * 60;
+/// The longest delay in minutes: a week.
+const MAX_MINUTES: f64 = 7.0 * 24.0 * 60.0;
+
+/// The `[sleep]` section of the configuration file.
+#[derive(Debug, Clone, Deserialize)]
+#[serde(default)]
+pub struct SleepConfig {
+    /// How long the fade-out takes, in milliseconds.
+    pub fade: u64,
+    /// How much the extend button adds, in minutes.
+    pub extension: u64,
+}
+
+impl Default for SleepConfig {
+    fn default() -> Self {
+        Self {
+            fade: 30_000,
+            extension: 15,
+        }
+    }
+}
+
+/// What the timer silences.
+#[derive(Debug, Clone, PartialEq)]
+pub enum SleepTarget {
+    Sink,
+    /// The streams of these applications, by name.
+    Applications(Vec<String>),
+}
+
+#[derive(Debug)]
+pub struct SleepTimer {
+    target: SleepTarget,
+    /// Whether the media players of the target are paused too.
+    pauses: bool,
+    deadline: Instant,
+}
+
+impl SleepTimer {
+    pub fn new(target: SleepTarget, pauses: bool, delay: Duration) -> Self {
+        Self {
+            target,
+            pauses,
+            deadline: Instant::now() + delay,
+        }
+    }
+
+    pub fn target(&self) -> &SleepTarget {
+        &self.target
+    }
+
+    pub fn pauses(&self) -> bool {
+        self.pauses
+    }
+
+    pub fn remaining(&self) -> Duration {
+        self.deadline.saturating_duration_since(Instant::now())
+    }
+
+    pub fn is_due(&self) -> bool {
+        Instant::now() >= self.deadline
+    }
+
+    pub fn extend(&mut self, by: Duration) {
+        self.deadline += by;
+    }
+
+    /// The remaining time as `H:MM:SS` or `M:SS`, for the header.
+    pub fn countdown(&self) -> String {
+        let seconds = self.remaining().as_secs();
+        let (hours, minutes, seconds) = (seconds / 3600, seconds / 60 % 60, seconds % 60);
+
+        if hours > 0 {
+            format!("{}:{:02}:{:02}", hours, minutes, seconds)
+        } else {
+            format!("{}:{:02}", minutes, seconds)
+        }
+    }
+}
+
+/// Parses the delay of the timer: a number of minutes up to a week, e.g.
+/// `30`, or a wall-clock time, e.g. `23:30`.
+pub fn parse_delay(value: &str) -> Result<Duration, String> {
+    let value = value.trim();
+    let invalid = || format!("Invalid delay \"{}\": give minutes or HH:MM", value);
+
+    let mut parts = value.splitn(2, ':');
+    let first = parts.next().unwrap_or_default();
+
+    match parts.next() {
+        Some(minute) => {
+            let hour: i64 = first.parse().map_err(|_| invalid())?;
+            let minute: i64 = minute.parse().map_err(|_| invalid())?;
+            if !(0..24).contains(&hour) || !(0..60).contains(&minute) {
+                return Err(invalid());
+            }
+            Ok(until_clock(hour, minute))
+        }
+        None => match first.parse::<f64>() {
+            Ok(minutes) if minutes > 0.0 && minutes <= MAX_MINUTES => {
+                Ok(Duration::from_secs_f64(minutes * 60.0))
+            }
+            _ => Err(invalid()),
+        },
+    }
+}
+
+/// The time until the next `hour:minute` of the local time.
+fn until_clock(hour: i64, minute: i64) -> Duration {
+    // Safe: `localtime_r` only writes into `local`.
+    let local = unsafe {
+        let now = libc::time(ptr::null_mut());
+        let mut local: libc::tm = mem::zeroed();
+        libc::localtime_r(&now, &mut local);
+        local
+    };
+
+    let now = local.tm_hour as i64 * 3600 + local.tm_min as i64 * 60 + local.tm_sec as i64;
+    let mut seconds = hour * 3600 + minute * 60 - now;
+    if seconds <= 0 {
+        seconds += SECONDS_PER_DAY;
+    }
+
+    Duration::from_secs(seconds as u64)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const DAY: Duration = Duration::from_secs(SECONDS_PER_DAY as u64);
+
+    #[test]
+    fn parses_minutes() {
+        assert_eq!(parse_delay("30"), Ok(Duration::from_secs(30 * 60)));
+        assert_eq!(parse_delay(" 1.5 "), Ok(Duration::from_secs(90)));
+        assert_eq!(
+            parse_delay("10080"),
+            Ok(Duration::from_secs(7 * 24 * 60 * 60))
+        );
+    }
+
+    #[test]
+    fn rejects_zero_negative_and_invalid_minutes() {
+        for value in &["0", "0.0", "-5", "", "soon", "inf", "NaN", "1e300", "10081"] {
+            assert!(parse_delay(value).is_err(), "{} was accepted", value);
+        }
+    }
+
+    #[test]
+    fn parses_wall_clock_times_as_the_next_one_to_come() {
+        for value in &["00:00", "23:59", "12:30", " 7:05 "] {
+            let delay = parse_delay(value).unwrap();
+            assert!(
+                delay > Duration::from_secs(0) && delay <= DAY,
+                "{} is {:?} away",
+                value,
+                delay
+            );
+        }
+    }
+
+    #[test]
+    fn rejects_invalid_wall_clock_times() {
+        for value in &[
+            "24:00", "12:60", "-1:30", "12:", ":30", "12:30:00", "noon:00",
+        ] {
+            assert!(parse_delay(value).is_err(), "{} was accepted", value);
+        }
+    }
+
+    #[test]
+    fn counts_down_and_extends() {
+        let mut timer = SleepTimer::new(SleepTarget::Sink, false, Duration::from_secs(0));
+        assert!(timer.is_due());
+        assert_eq!(timer.countdown(), "0:00");
+
+        timer.extend(Duration::from_secs(2 * 3600 + 90));
+        assert!(!timer.is_due());
+        assert!(timer.countdown().starts_with("2:01:"));
+    }
+}
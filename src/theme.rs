@@ -0,0 +1,487 @@
+//! Colours of the user interface.
+//!
+//! A [`Theme`] is built from a [`Palette`] and hands out the style sheets
+//! of every widget used by the mixer. The palette is one of the built-in
+//! dark and light ones, picked by name or by the desktop colour scheme,
+//! with the colours of the configuration file put on top of it.
+//!
+//! [`Theme`]: struct.Theme.html
+//! [`Palette`]: struct.Palette.html
+use std::env;
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use serde::Deserialize;
+
+use iced::{button, container, progress_bar, slider, Background, Color, Vector};
+
+use crate::button::toggle;
+
+const COLOR_SCHEME_FILE: &str = "color-scheme";
+const GTK_SETTINGS_FILE: &str = "gtk-3.0/settings.ini";
+const GTK_PREFER_DARK: &str = "gtk-application-prefer-dark-theme";
+
+/// The colours every style sheet is derived from.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Palette {
+    pub background: Color,
+    pub surface: Color,
+    pub text: Color,
+    pub accent: Color,
+    pub muted: Color,
+    pub border: Color,
+}
+
+impl Palette {
+    pub const DARK: Palette = Palette {
+        background: Color { r: 0.13, g: 0.13, b: 0.15, a: 1.0 },
+        surface: Color { r: 0.22, g: 0.22, b: 0.25, a: 1.0 },
+        text: Color { r: 0.90, g: 0.90, b: 0.90, a: 1.0 },
+        accent: Color { r: 0.35, g: 0.60, b: 0.95, a: 1.0 },
+        muted: Color { r: 0.80, g: 0.30, b: 0.30, a: 1.0 },
+        border: Color { r: 0.35, g: 0.35, b: 0.38, a: 1.0 },
+    };
+
+    pub const LIGHT: Palette = Palette {
+        background: Color { r: 0.97, g: 0.97, b: 0.97, a: 1.0 },
+        surface: Color { r: 0.87, g: 0.87, b: 0.87, a: 1.0 },
+        text: Color { r: 0.10, g: 0.10, b: 0.10, a: 1.0 },
+        accent: Color { r: 0.20, g: 0.45, b: 0.85, a: 1.0 },
+        muted: Color { r: 0.87, g: 0.33, b: 0.33, a: 1.0 },
+        border: Color { r: 0.70, g: 0.70, b: 0.70, a: 1.0 },
+    };
+}
+
+/// The built-in palette to start from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum ThemeName {
+    /// Follow the desktop colour scheme.
+    Auto,
+    Dark,
+    Light,
+}
+
+impl Default for ThemeName {
+    fn default() -> Self {
+        ThemeName::Auto
+    }
+}
+
+/// Colours overriding the ones of the built-in palette, as `#rrggbb`.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct Colors {
+    pub background: Option<String>,
+    pub surface: Option<String>,
+    pub text: Option<String>,
+    pub accent: Option<String>,
+    pub muted: Option<String>,
+    pub border: Option<String>,
+}
+
+/// The `[theme]` section of the configuration file.
+#[derive(Debug, Clone, Default, Deserialize)]
+#[serde(default)]
+pub struct ThemeConfig {
+    pub name: ThemeName,
+    /// A file holding the freedesktop `color-scheme` value, read instead
+    /// of the default locations.
+    pub color_scheme_file: Option<PathBuf>,
+    pub colors: Colors,
+}
+
+/// The freedesktop `org.freedesktop.appearance` `color-scheme` setting.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ColorScheme {
+    NoPreference,
+    PreferDark,
+    PreferLight,
+}
+
+impl ColorScheme {
+    /// Reads the colour scheme of the desktop.
+    ///
+    /// The portal value is looked up in `file`, or in the `color-scheme`
+    /// file of the configuration directory, which desktop-specific scripts
+    /// (or tests) can keep in sync with the settings portal. The GTK
+    /// settings are used as a fallback.
+    pub fn detect(file: Option<&Path>) -> Self {
+        let file = file
+            .map(Path::to_path_buf)
+            .or_else(|| crate::config::directory().map(|dir| dir.join(COLOR_SCHEME_FILE)));
+
+        if let Some(scheme) = file
+            .and_then(|file| fs::read_to_string(file).ok())
+            .and_then(|value| Self::parse(&value))
+        {
+            return scheme;
+        }
+
+        if let Ok(theme) = env::var("GTK_THEME") {
+            if theme.ends_with(":dark") {
+                return ColorScheme::PreferDark;
+            }
+        }
+
+        dirs::config_dir()
+            .and_then(|dir| fs::read_to_string(dir.join(GTK_SETTINGS_FILE)).ok())
+            .and_then(|settings| {
+                settings
+                    .lines()
+                    .filter_map(|line| {
+                        let mut pair = line.splitn(2, '=');
+                        Some((pair.next()?.trim(), pair.next()?.trim()))
+                    })
+                    .find(|(key, _)| *key == GTK_PREFER_DARK)
+                    .map(|(_, value)| match value {
+                        "1" | "true" => ColorScheme::PreferDark,
+                        _ => ColorScheme::PreferLight,
+                    })
+            })
+            .unwrap_or(ColorScheme::NoPreference)
+    }
+
+    /// Parses either the numeric portal value or its name.
+    fn parse(value: &str) -> Option<Self> {
+        match value.trim().trim_matches(|c| c == '\'' || c == '"') {
+            "0" | "default" => Some(ColorScheme::NoPreference),
+            "1" | "prefer-dark" => Some(ColorScheme::PreferDark),
+            "2" | "prefer-light" => Some(ColorScheme::PreferLight),
+            _ => None,
+        }
+    }
+}
+
+/// The theme of the configuration, with its colours parsed once, so that
+/// following the desktop only reads the colour scheme again.
+#[derive(Debug, Clone)]
+pub struct ThemeSource {
+    name: ThemeName,
+    color_scheme_file: Option<PathBuf>,
+    colors: Overrides,
+}
+
+impl ThemeSource {
+    pub fn new(config: &ThemeConfig) -> Self {
+        Self {
+            name: config.name,
+            color_scheme_file: config.color_scheme_file.clone(),
+            colors: Overrides::parse(&config.colors),
+        }
+    }
+
+    /// Whether the palette changes with the desktop colour scheme.
+    pub fn follows_desktop(&self) -> bool {
+        self.name == ThemeName::Auto
+    }
+
+    /// Builds the theme, reading the desktop colour scheme when it is
+    /// followed.
+    pub fn theme(&self) -> Theme {
+        let palette = match self.name {
+            ThemeName::Dark => Palette::DARK,
+            ThemeName::Light => Palette::LIGHT,
+            ThemeName::Auto => match ColorScheme::detect(self.color_scheme_file.as_deref()) {
+                ColorScheme::PreferDark => Palette::DARK,
+                ColorScheme::PreferLight | ColorScheme::NoPreference => Palette::LIGHT,
+            },
+        };
+
+        Theme {
+            palette: self.colors.apply(palette),
+        }
+    }
+}
+
+/// The colours of the configuration put on top of the built-in palette.
+#[derive(Debug, Clone, Copy, Default)]
+struct Overrides {
+    background: Option<Color>,
+    surface: Option<Color>,
+    text: Option<Color>,
+    accent: Option<Color>,
+    muted: Option<Color>,
+    border: Option<Color>,
+}
+
+impl Overrides {
+    /// Parses the colours, warning about and ignoring the invalid ones.
+    fn parse(colors: &Colors) -> Self {
+        let parse = |value: &Option<String>| {
+            let value = value.as_ref()?;
+            let color = parse_color(value);
+            if color.is_none() {
+                log::warn!("Invalid colour \"{}\" in the theme, ignoring it", value);
+            }
+            color
+        };
+
+        Self {
+            background: parse(&colors.background),
+            surface: parse(&colors.surface),
+            text: parse(&colors.text),
+            accent: parse(&colors.accent),
+            muted: parse(&colors.muted),
+            border: parse(&colors.border),
+        }
+    }
+
+    fn apply(&self, palette: Palette) -> Palette {
+        Palette {
+            background: self.background.unwrap_or(palette.background),
+            surface: self.surface.unwrap_or(palette.surface),
+            text: self.text.unwrap_or(palette.text),
+            accent: self.accent.unwrap_or(palette.accent),
+            muted: self.muted.unwrap_or(palette.muted),
+            border: self.border.unwrap_or(palette.border),
+        }
+    }
+}
+
+/// The style sheets of the user interface.
+#[derive(Debug, Clone, Copy, PartialEq)]
+pub struct Theme {
+    pub palette: Palette,
+}
+
+impl Theme {
+    /// Builds the theme described by the configuration.
+    pub fn from_config(config: &ThemeConfig) -> Self {
+        ThemeSource::new(config).theme()
+    }
+
+    pub fn container(&self) -> Container {
+        Container(self.palette)
+    }
+
+    pub fn slider(&self) -> Slider {
+        Slider(self.palette)
+    }
+
+    pub fn button(&self) -> Button {
+        Button(self.palette)
+    }
+
+    pub fn toggle(&self) -> Toggle {
+        Toggle(self.palette)
+    }
+
+    /// The style of a volume bar, drawn in the mute colour when `mute`.
+    pub fn progress_bar(&self, mute: bool) -> ProgressBar {
+        ProgressBar(self.palette, mute)
+    }
+}
+
+impl Default for Theme {
+    fn default() -> Self {
+        Self {
+            palette: Palette::LIGHT,
+        }
+    }
+}
+
+pub struct Container(Palette);
+
+impl container::StyleSheet for Container {
+    fn style(&self) -> container::Style {
+        container::Style {
+            text_color: Some(self.0.text),
+            background: Some(Background::Color(self.0.background)),
+            border_radius: 0,
+            border_width: 0,
+            border_color: Color::TRANSPARENT,
+        }
+    }
+}
+
+pub struct Slider(Palette);
+
+impl slider::StyleSheet for Slider {
+    fn active(&self) -> slider::Style {
+        slider::Style {
+            rail_colors: (self.0.accent, self.0.border),
+            handle: slider::Handle {
+                shape: slider::HandleShape::Rectangle {
+                    width: 8,
+                    border_radius: 4,
+                },
+                color: self.0.surface,
+                border_width: 1,
+                border_color: self.0.border,
+            },
+        }
+    }
+
+    fn hovered(&self) -> slider::Style {
+        let active = self.active();
+
+        slider::Style {
+            handle: slider::Handle {
+                border_color: self.0.accent,
+                ..active.handle
+            },
+            ..active
+        }
+    }
+
+    fn dragging(&self) -> slider::Style {
+        let active = self.active();
+
+        slider::Style {
+            handle: slider::Handle {
+                color: self.0.accent,
+                border_color: self.0.accent,
+                ..active.handle
+            },
+            ..active
+        }
+    }
+}
+
+pub struct Button(Palette);
+
+impl button::StyleSheet for Button {
+    fn active(&self) -> button::Style {
+        button::Style {
+            shadow_offset: Vector::new(0.0, 0.0),
+            background: Some(Background::Color(self.0.surface)),
+            border_radius: 2,
+            border_width: 1,
+            border_color: self.0.border,
+            text_color: self.0.text,
+        }
+    }
+}
+
+pub struct Toggle(Palette);
+
+impl toggle::StyleSheet for Toggle {
+    fn on(&self) -> toggle::Style {
+        toggle::Style {
+            shadow_offset: Vector::new(0.0, 0.0),
+            background: Some(Background::Color(self.0.muted)),
+            border_radius: 2,
+            border_width: 1,
+            border_color: self.0.border,
+            text_color: Color::WHITE,
+        }
+    }
+
+    fn off(&self) -> toggle::Style {
+        toggle::Style {
+            shadow_offset: Vector::new(0.0, 0.0),
+            background: Some(Background::Color(self.0.surface)),
+            border_radius: 2,
+            border_width: 1,
+            border_color: self.0.border,
+            text_color: self.0.text,
+        }
+    }
+}
+
+pub struct ProgressBar(Palette, bool);
+
+impl progress_bar::StyleSheet for ProgressBar {
+    fn style(&self) -> progress_bar::Style {
+        let bar = if self.1 { self.0.muted } else { self.0.accent };
+
+        progress_bar::Style {
+            background: Background::Color(self.0.surface),
+            bar: Background::Color(bar),
+            border_radius: 4,
+        }
+    }
+}
+
+/// Parses a `#rrggbb` or `#rrggbbaa` colour.
+fn parse_color(value: &str) -> Option<Color> {
+    let hex = value.trim().trim_start_matches('#');
+    if hex.len() != 6 && hex.len() != 8 {
+        return None;
+    }
+
+    let channel = |index: usize| {
+        hex.get(index * 2..index * 2 + 2)
+            .and_then(|channel| u8::from_str_radix(channel, 16).ok())
+            .map(|channel| f32::from(channel) / 255.0)
+    };
+
+    Some(Color {
+        r: channel(0)?,
+        g: channel(1)?,
+        b: channel(2)?,
+        a: if hex.len() == 8 { channel(3)? } else { 1.0 },
+    })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::process;
+
+    #[test]
+    fn parses_colours_with_or_without_alpha() {
+        assert_eq!(
+            parse_color("#ff8000"),
+            Some(Color::from_rgb(1.0, 128.0 / 255.0, 0.0))
+        );
+        assert_eq!(
+            parse_color(" 00000080 "),
+            Some(Color::from_rgba(0.0, 0.0, 0.0, 128.0 / 255.0))
+        );
+    }
+
+    #[test]
+    fn rejects_invalid_colours() {
+        for value in &["", "#", "#fff", "#ff80001", "#gg8000", "#ff80 0", "red"] {
+            assert_eq!(parse_color(value), None, "{} was accepted", value);
+        }
+    }
+
+    #[test]
+    fn parses_the_portal_value_or_its_name() {
+        let schemes = [
+            ("0", ColorScheme::NoPreference),
+            ("default", ColorScheme::NoPreference),
+            ("1\n", ColorScheme::PreferDark),
+            ("'prefer-dark'", ColorScheme::PreferDark),
+            ("2", ColorScheme::PreferLight),
+            ("\"prefer-light\"", ColorScheme::PreferLight),
+        ];
+        for &(value, scheme) in &schemes {
+            assert_eq!(ColorScheme::parse(value), Some(scheme), "{}", value);
+        }
+        assert_eq!(ColorScheme::parse("3"), None);
+        assert_eq!(ColorScheme::parse("dark"), None);
+    }
+
+    #[test]
+    fn follows_the_desktop_under_the_colours_of_the_configuration() {
+        let file = std::env::temp_dir().join(format!("volume-controller-scheme-{}", process::id()));
+        let accent = Color::from_rgb(1.0, 0.0, 0.0);
+        let config = ThemeConfig {
+            name: ThemeName::Auto,
+            color_scheme_file: Some(file.clone()),
+            colors: Colors {
+                accent: Some("#ff0000".into()),
+                surface: Some("invalid".into()),
+                ..Colors::default()
+            },
+        };
+        let source = ThemeSource::new(&config);
+
+        fs::write(&file, "prefer-dark").unwrap();
+        let dark = source.theme().palette;
+        fs::write(&file, "prefer-light").unwrap();
+        let light = source.theme().palette;
+        let _ = fs::remove_file(&file);
+
+        assert!(source.follows_desktop());
+        assert_eq!(dark.background, Palette::DARK.background);
+        assert_eq!(dark.surface, Palette::DARK.surface);
+        assert_eq!(dark.accent, accent);
+        assert_eq!(light.background, Palette::LIGHT.background);
+        assert_eq!(light.accent, accent);
+    }
+}
@@ -0,0 +1,206 @@
+//! StatusNotifierItem tray icon of the default sink.
+//!
+//! The icon runs in its own process (`volume-controller-ui tray`) with its
+//! own PulseAudio context. It registers on the session bus given by
+//! `DBUS_SESSION_BUS_ADDRESS`, so it can be run against a locally launched
+//! `dbus-daemon`.
+//!
+//! Scrolling over the icon changes the volume, a middle click toggles the
+//! mute and a left click shows or hides the compact mixer popup.
+use std::cell::RefCell;
+use std::process::Child;
+use std::rc::Rc;
+use std::sync::mpsc::{self, Sender};
+use std::thread;
+use std::time::Duration;
+
+use ksni::menu::StandardItem;
+use ksni::{MenuItem, ToolTip, TrayService};
+
+use crate::cli;
+use crate::config::Config;
+use crate::updater::{update_fetch_sink, update_sink_mute, update_sink_volume, MainData, PulseHandler};
+
+const TRAY_ID: &str = "volume-controller-ui";
+const TRAY_TITLE: &str = "Volume Controller";
+const POLL_INTERVAL: Duration = Duration::from_millis(200);
+const MAX_VOLUME: u32 = 65536;
+/// The volume change of a single scroll step, 5%.
+const VOLUME_STEP: u32 = MAX_VOLUME / 20;
+
+enum Command {
+    Scroll(i32),
+    ToggleMute,
+    TogglePopup,
+    OpenMixer,
+    Quit,
+}
+
+struct Tray {
+    volume: u32,
+    mute: bool,
+    commands: Sender<Command>,
+}
+
+impl Tray {
+    fn send(&self, command: Command) {
+        // The receiver only goes away once the tray is shut down.
+        let _ = self.commands.send(command);
+    }
+}
+
+impl ksni::Tray for Tray {
+    fn id(&self) -> String {
+        TRAY_ID.into()
+    }
+
+    fn title(&self) -> String {
+        TRAY_TITLE.into()
+    }
+
+    fn icon_name(&self) -> String {
+        icon_name(self.volume, self.mute).into()
+    }
+
+    fn tool_tip(&self) -> ToolTip {
+        let status = if self.mute { " (muted)" } else { "" };
+
+        ToolTip {
+            title: format!("Volume {}%{}", self.volume * 100 / MAX_VOLUME, status),
+            ..Default::default()
+        }
+    }
+
+    fn activate(&mut self, _x: i32, _y: i32) {
+        self.send(Command::TogglePopup);
+    }
+
+    fn secondary_activate(&mut self, _x: i32, _y: i32) {
+        self.send(Command::ToggleMute);
+    }
+
+    fn scroll(&mut self, delta: i32, dir: &str) {
+        if dir == "vertical" {
+            self.send(Command::Scroll(delta));
+        }
+    }
+
+    fn menu(&self) -> Vec<MenuItem<Self>> {
+        vec![
+            StandardItem {
+                label: "Open mixer".into(),
+                activate: Box::new(|tray: &mut Self| tray.send(Command::OpenMixer)),
+                ..Default::default()
+            }
+            .into(),
+            MenuItem::Separator,
+            StandardItem {
+                label: "Quit".into(),
+                activate: Box::new(|tray: &mut Self| tray.send(Command::Quit)),
+                ..Default::default()
+            }
+            .into(),
+        ]
+    }
+}
+
+/// Runs the tray icon until it is quit from its menu.
+pub fn run() {
+    let mut handler = match PulseHandler::new() {
+        Some(handler) => handler,
+        None => return,
+    };
+
+    let (sender, commands) = mpsc::channel();
+    let service = TrayService::new(Tray {
+        volume: 0,
+        mute: false,
+        commands: sender,
+    });
+    let tray = service.handle();
+    service.spawn();
+
+    let limits = Config::load().limits;
+    let sink = Rc::new(RefCell::new(MainData::default()));
+    let mut shown = (0, false);
+    let mut popup: Option<Child> = None;
+
+    loop {
+        for command in commands.try_iter() {
+            let data = sink.borrow().clone();
+
+            match command {
+                Command::Scroll(delta) => {
+                    let max_volume = limits
+                        .sink(&data.name)
+                        .map_or(MAX_VOLUME, |cap| cap.min(MAX_VOLUME));
+                    let volume = if delta > 0 {
+                        data.volume + VOLUME_STEP
+                    } else {
+                        data.volume.saturating_sub(VOLUME_STEP)
+                    };
+                    let volume = volume.min(max_volume);
+                    sink.borrow_mut().volume = volume;
+                    update_sink_volume(&mut handler, volume, data.volume);
+                }
+                Command::ToggleMute => {
+                    sink.borrow_mut().mute = !data.mute;
+                    update_sink_mute(&mut handler, !data.mute, data.mute);
+                }
+                Command::TogglePopup => {
+                    popup = match popup.take() {
+                        Some(mut child) if is_running(&mut child) => {
+                            let _ = child.kill();
+                            let _ = child.wait();
+                            None
+                        }
+                        _ => cli::spawn_self(&["popup"]),
+                    }
+                }
+                Command::OpenMixer => {
+                    cli::spawn_self(&["window"]);
+                }
+                Command::Quit => {
+                    if let Some(mut child) = popup {
+                        let _ = child.kill();
+                        let _ = child.wait();
+                    }
+                    tray.shutdown();
+                    return;
+                }
+            }
+        }
+
+        handler.poll();
+        update_fetch_sink(&mut handler, sink.clone());
+
+        let data = sink.borrow().clone();
+        if (data.volume, data.mute) != shown {
+            shown = (data.volume, data.mute);
+            tray.update(|tray| {
+                tray.volume = data.volume;
+                tray.mute = data.mute;
+            });
+        }
+
+        thread::sleep(POLL_INTERVAL);
+    }
+}
+
+/// The freedesktop icon matching the volume.
+fn icon_name(volume: u32, mute: bool) -> &'static str {
+    match volume * 100 / MAX_VOLUME {
+        _ if mute => "audio-volume-muted",
+        0 => "audio-volume-muted",
+        1..=33 => "audio-volume-low",
+        34..=66 => "audio-volume-medium",
+        _ => "audio-volume-high",
+    }
+}
+
+fn is_running(child: &mut Child) -> bool {
+    match child.try_wait() {
+        Ok(None) => true,
+        _ => false,
+    }
+}
@@ -1,47 +1,420 @@
 use std::rc::Rc;
-use std::cell::RefCell;
-
-#[cfg(feature = "time")]
-use std::time::SystemTime;
+use std::cell::{Cell, RefCell};
+use std::time::{Duration, Instant};
+use std::sync::mpsc::Receiver;
 
 use crate::updater::{
-    SinkInputData, MainData, PulseHandler,
+    SinkInputData, SourceOutputData, MainData, PulseHandler, Rejected, Target, Change,
     update_sink_inputs, update_sink_input_volume_by_id, update_sink_input_mute_by_id,
     update_fetch_sink,   update_sink_volume,   update_sink_mute,
     update_fetch_source, update_source_volume, update_source_mute,
+    fetch_source_outputs, fetch_cards, fetch_sinks, fetch_sources, fetch_details,
+    fetch_server_info, fetch_client_count, fetch_modules, fetch_sink_inputs,
+    update_sink_volume_by_index,
+    update_card_profile, update_sink_port, update_source_port,
+    Availability, CardData, Details, DeviceData, ServerData, Stats, ROUND_TRIP,
 };
 
-use crate::button::WgpuButton as Button;
-use crate::button;
+use crate::button::{self, Button, Toggle};
+use crate::clipboard;
+use crate::config::Config;
+use crate::ducking::Ducking;
+use crate::fade::{FadeConfig, Fader};
+use crate::filter::{self, StreamsConfig};
+use crate::group::{self, Group, Scaling};
+use crate::history::History;
+use crate::instance::Request;
+use crate::layout::{self, Layout};
+use crate::limit::LimitConfig;
+use crate::link::{LinkMode, Links};
+use crate::modules::{self, Setup, Tracked};
+use crate::mpris::{self, Mpris, PlaybackStatus, Player};
+use crate::osd;
+use crate::ptt::{PttMode, PushToTalk};
+use crate::scene::{self, Scene};
+use crate::sleep::{self, SleepConfig, SleepTarget, SleepTimer};
+use crate::theme::{Theme, ThemeSource};
+use crate::vertical_slider::{self, VerticalSlider};
 
 use iced::{
-    slider, scrollable,
-    Slider, Scrollable,
-    Sandbox, Element, Container, Column, Length, Text, Row,
-    Align, HorizontalAlignment, VerticalAlignment
+    slider, scrollable, text_input, executor, time,
+    Slider, Scrollable, TextInput,
+    Application, Command, Subscription, Element, Container, Column, Length, Text, Row,
+    Align, HorizontalAlignment, VerticalAlignment, Space
 };
+use iced_native::{keyboard, subscription, window, Event};
+use pulse::context::subscribe::{Facility, InterestMaskSet};
 
 const MAX_VOLUME: u32 = 65536;
-const MAX_VOLUME_FLOAT: f32 = 65536.0;
-const MUTE_BUTTON_SIZE: u16 = 100;
-const PROCENT_STATUS_SIZE: u16 = 100;
-const APPLICATION_NAME_SIZE: u16 = 200;
 const APPLICATION_NAME: &'static str = "Volume Controller";
 const SINK_NAME:        &'static str = "System Volume";
 const SOURCE_NAME:      &'static str = "Microphone";
+const MUTED_LABEL:      &'static str = "Muted";
+const UNMUTED_LABEL:    &'static str = "Unmuted";
+const MUTED_ICON:       &'static str = "\u{00D7}";
+const UNMUTED_ICON:     &'static str = "\u{2669}";
+const PENDING_MARK:     &'static str = " \u{2026}";
+const MIC_ICON:         &'static str = "\u{2022}";
+const MIC_LIVE_LABEL:   &'static str = "Live";
+const FADE_LABEL:       &'static str = "Fade";
+const FADE_ICON:        &'static str = "\u{2212}";
+const STOP_FADE_LABEL:  &'static str = "Stop";
+const STOP_FADE_ICON:   &'static str = "\u{00D7}";
+const SLEEP_ICON:       &'static str = "Zz";
+const PREVIOUS_ICON:    &'static str = "\u{00AB}";
+const PLAY_ICON:        &'static str = "\u{203A}";
+const PAUSE_ICON:       &'static str = "||";
+const NEXT_ICON:        &'static str = "\u{00BB}";
+const INSPECT_LABEL:    &'static str = "Info";
+const INSPECT_ICON:     &'static str = "i";
+const HIDE_LABEL:       &'static str = "Hide";
+const HIDE_ICON:        &'static str = "\u{00F8}";
+const UNHIDE_LABEL:     &'static str = "Unhide";
+const UNHIDE_ICON:      &'static str = "\u{25CA}";
+const EXPAND_LABEL:     &'static str = "Expand";
+const EXPAND_ICON:      &'static str = "+";
+const COLLAPSE_LABEL:   &'static str = "Collapse";
+const COLLAPSE_ICON:    &'static str = "\u{2013}";
+const MEMBER_MARK:      &'static str = "\u{00B7}";
+const LINK_LABEL:       &'static str = "Link";
+const LINK_ICON:        &'static str = "\u{221E}";
+const POLL_INTERVAL:    Duration     = Duration::from_millis(100);
+const THEME_INTERVAL:   Duration     = Duration::from_secs(2);
+const NOTICE_DURATION:  Duration     = Duration::from_secs(5);
+
+pub struct Flags {
+    pub config:        Config,
+    /// The requests of the later launches, for the single instance.
+    pub requests:      Option<Receiver<Request>>,
+}
 
 pub struct UserInterface {
     pulse_handler:     PulseHandler,
     scroll:            scrollable::State,
+    requests:          Option<Receiver<Request>>,
+    focused:           Option<String>,
+    /// Set when a toggle hid the window, which keeps running.
+    is_hidden:         bool,
+    ptt:               PushToTalk,
+    ducking:           Ducking,
+    fader:             Fader,
+    fade_config:       FadeConfig,
+    sleep:             Option<SleepTimer>,
+    /// The media players to pause once the fade of the sleep timer is over.
+    sleep_pause:       Option<(Instant, Vec<String>)>,
+    sleep_config:      SleepConfig,
+    mpris:             Option<Mpris>,
+
+    theme:             Theme,
+    theme_source:      ThemeSource,
+    theme_checked:     Instant,
 
-    sink_input_uis:    Vec<(slider::State, button::State)>,
+    layout:            Layout,
+    layout_buttons:    [button::State; 3],
+
+    page:              Page,
+    page_buttons:      [button::State; 6],
+    devices:           DeviceState,
+    /// Set when a card, sink or source changed, for the devices page.
+    devices_changed:   Rc<Cell<bool>>,
+    /// Set when a sink changed, to check the caps of the sinks.
+    sinks_changed:     Rc<Cell<bool>>,
+    /// Set when a recording stream came or went, for the ducking.
+    source_outputs_changed: Rc<Cell<bool>>,
+    limits:            LimitConfig,
+    /// The last volume brought back under its cap, and when.
+    limit_notice:      Option<(String, Instant)>,
+    /// The modules loaded from the mixer.
+    modules:           Vec<Tracked>,
+    scenes:            SceneState,
+    server:            ServerState,
+    sleep_ui:          SleepState,
+    history:           History,
+    history_ui:        HistoryState,
+
+    sink_input_uis:    Vec<RowState>,
     sink_input_datas:  Rc<RefCell<Vec<SinkInputData>>>,
+    source_outputs:    Vec<SourceOutputData>,
+    streams:           StreamsConfig,
+    filter:            FilterState,
+    group_uis:         Vec<RowState>,
+    /// The applications whose group shows its streams.
+    expanded:          Vec<String>,
+    /// The drag of a group slider.
+    scaling:           Option<Scaling>,
+    links:             Links,
+    link_ui:           LinkState,
 
-    sink_ui:           (slider::State, button::State),
+    sink_ui:           RowState,
     sink_data:         Rc<RefCell<MainData>>,
 
-    source_ui:         (slider::State, button::State),
+    source_ui:         RowState,
     source_data:       Rc<RefCell<MainData>>,
+
+    inspector:         InspectorState,
+}
+
+/// What the window shows below the header.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Page {
+    Streams,
+    Devices,
+    Scenes,
+    History,
+    Sleep,
+    Server,
+}
+
+impl Page {
+    const ALL: [Page; 6] = [Page::Streams,
+			    Page::Devices,
+			    Page::Scenes,
+			    Page::History,
+			    Page::Sleep,
+			    Page::Server];
+
+    fn name(self) -> &'static str {
+	match self {
+	    Page::Streams => "Streams",
+	    Page::Devices => "Devices",
+	    Page::Scenes  => "Scenes",
+	    Page::History => "History",
+	    Page::Sleep   => "Sleep",
+	    Page::Server  => "Server",
+	}
+    }
+}
+
+/// The widget states of the history page.
+#[derive(Default)]
+pub struct HistoryState {
+    scroll:            scrollable::State,
+    undo:              button::State,
+    redo:              button::State,
+}
+
+/// The widget states of the scenes page.
+#[derive(Default)]
+pub struct SceneState {
+    scroll:            scrollable::State,
+    name_input:        text_input::State,
+    name:              String,
+    save:              button::State,
+    entries:           Vec<SceneEntry>,
+    /// The outcome of the last action, line by line.
+    report:            Vec<String>,
+}
+
+/// The widget states of the server page.
+#[derive(Default)]
+pub struct ServerState {
+    scroll:            scrollable::State,
+    refresh:           button::State,
+    info:              Option<ServerData>,
+    /// The number of objects of each kind on the server.
+    counts:            Vec<(&'static str, usize)>,
+}
+
+/// The details pane of the streams page.
+#[derive(Default)]
+pub struct InspectorState {
+    scroll:            scrollable::State,
+    refresh:           button::State,
+    copy:              button::State,
+    close:             button::State,
+    target:            Option<Target>,
+    details:           Option<Details>,
+    report:            Option<String>,
+}
+
+/// The widget states of the devices page.
+#[derive(Default)]
+pub struct DeviceState {
+    scroll:            scrollable::State,
+    cards:             Vec<CardEntry>,
+    sinks:             Vec<PortEntry>,
+    sources:           Vec<PortEntry>,
+    modules:           ModuleState,
+}
+
+/// A kind of virtual device.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ModuleKind {
+    NullSink,
+    Loopback,
+    CombineSink,
+    EchoCancel,
+}
+
+impl Default for ModuleKind {
+    fn default() -> Self {
+	ModuleKind::NullSink
+    }
+}
+
+impl ModuleKind {
+    const ALL: [ModuleKind; 4] = [ModuleKind::NullSink,
+				  ModuleKind::Loopback,
+				  ModuleKind::CombineSink,
+				  ModuleKind::EchoCancel];
+
+    fn name(self) -> &'static str {
+	match self {
+	    ModuleKind::NullSink    => "Null sink",
+	    ModuleKind::Loopback    => "Loopback",
+	    ModuleKind::CombineSink => "Combined sink",
+	    ModuleKind::EchoCancel  => "Echo cancel",
+	}
+    }
+
+    fn has_name(self) -> bool {
+	self != ModuleKind::Loopback
+    }
+
+    fn has_source(self) -> bool {
+	self == ModuleKind::Loopback || self == ModuleKind::EchoCancel
+    }
+}
+
+/// The form creating virtual devices, on the devices page.
+#[derive(Default)]
+pub struct ModuleState {
+    kind:              ModuleKind,
+    kind_buttons:      [button::State; 4],
+    name_input:        text_input::State,
+    name:              String,
+    source_input:      text_input::State,
+    source:            String,
+    sink_input:        text_input::State,
+    /// One sink name, or several separated by commas for a combined sink.
+    sink:              String,
+    recreate:          bool,
+    recreate_toggle:   button::State,
+    create:            button::State,
+    remove:            Vec<button::State>,
+    report:            Option<String>,
+}
+
+pub struct CardEntry {
+    card:              CardData,
+    profiles:          Vec<button::State>,
+}
+
+/// A sink or source, with its ports.
+pub struct PortEntry {
+    device:            DeviceData,
+    ports:             Vec<button::State>,
+}
+
+/// The widget states of the sleep page.
+#[derive(Default)]
+pub struct SleepState {
+    scroll:            scrollable::State,
+    delay_input:       text_input::State,
+    delay:             String,
+    start:             button::State,
+    extend:            button::State,
+    cancel:            button::State,
+    sink:              button::State,
+    /// Whether the timer silences the chosen applications instead of the
+    /// default sink.
+    applications_only: bool,
+    applications:      Vec<SleepApplication>,
+    /// Whether the media players are paused too.
+    pauses:            bool,
+    pause_toggle:      button::State,
+    report:            Option<String>,
+}
+
+pub struct SleepApplication {
+    name:              String,
+    toggle:            button::State,
+    is_selected:       bool,
+}
+
+pub struct SceneEntry {
+    name:              String,
+    apply:             button::State,
+    diff:              button::State,
+}
+
+/// The search and the toggles above the streams.
+#[derive(Default)]
+pub struct FilterState {
+    search_input:      text_input::State,
+    search:            String,
+    /// Whether the hidden streams are shown anyway.
+    show_hidden:       bool,
+    reveal_toggle:     button::State,
+    events_toggle:     button::State,
+    own_toggle:        button::State,
+    group_toggle:      button::State,
+}
+
+/// The rows chosen to be linked, and the bar linking them.
+#[derive(Default)]
+pub struct LinkState {
+    selected:          Vec<Target>,
+    absolute:          button::State,
+    relative:          button::State,
+    cancel:            button::State,
+}
+
+/// The widget states of a row, for every layout.
+#[derive(Default)]
+pub struct RowState {
+    slider:            slider::State,
+    vertical_slider:   vertical_slider::State,
+    mute:              button::State,
+    fade:              button::State,
+    previous:          button::State,
+    play_pause:        button::State,
+    next:              button::State,
+    inspect:           button::State,
+    hide:              button::State,
+    expand:            button::State,
+    link:              button::State,
+}
+
+/// The widget states a row uses.
+#[derive(Debug, Clone, Copy)]
+enum Slot {
+    Sink,
+    Source,
+    /// A stream, by position in the stream list.
+    Stream(usize),
+    /// The row of an application with several streams, in display order.
+    Group(usize),
+}
+
+/// What a row shows, independently of the layout.
+struct Channel {
+    name:              String,
+    volume:            u32,
+    is_mute:           bool,
+    is_pending:        bool,
+    is_focused:        bool,
+    /// Whether the mute is driven by something else than the toggle.
+    is_locked:         bool,
+    is_fading:         bool,
+    /// Whether the application is hidden, its row only shown as asked.
+    is_hidden:         bool,
+    /// The application of a stream or group row, which can be hidden.
+    application:       Option<String>,
+    /// Whether the row of a group shows its streams.
+    expanded:          Option<bool>,
+    /// Whether the row is chosen to be linked.
+    is_selected:       bool,
+    /// The number of the link of the row.
+    link:              Option<usize>,
+    /// The top of the slider, lower than the maximum for a capped row.
+    max_volume:        u32,
+    slot:              Slot,
+    target:            Target,
+    /// The media player of an application row.
+    player:            Option<Player>,
+    on_slide:          Box<dyn Fn(u32) -> Message>,
+    on_toggle:         Box<dyn Fn(bool) -> Message>,
 }
 
 #[derive(Debug, Clone)]
@@ -52,176 +425,656 @@ pub enum Message {
     SinkMuteButtonPressed(bool),
     SourceSliderChanged(u32),
     SourceMuteButtonPressed(bool),
+    FadeOutPressed(Target),
+    PlayerAction(String, mpris::Action),
+    CardProfileSelected(u32, String),
+    SinkPortSelected(u32, String),
+    SourcePortSelected(u32, String),
+    ModuleKindSelected(ModuleKind),
+    ModuleNameChanged(String),
+    ModuleSourceChanged(String),
+    ModuleSinkChanged(String),
+    ModuleRecreateToggled(bool),
+    ModuleCreated,
+    ModuleRemoved(usize),
+    Inspect(Target),
+    InspectorRefreshed,
+    InspectorCopied,
+    InspectorClosed,
+    SearchChanged(String),
+    StreamHidden(String, bool),
+    HideEventsToggled(bool),
+    HideOwnToggled(bool),
+    ShowHiddenToggled(bool),
+    GroupingToggled(bool),
+    GroupSliderChanged(String, u32),
+    GroupMuteButtonPressed(String, bool),
+    GroupExpanded(String, bool),
+    LinkToggled(Target, bool),
+    LinkCreated(LinkMode),
+    LinkCancelled,
+    ServerRefreshed,
+    LayoutSelected(Layout),
+    PageSelected(Page),
+    SceneNameChanged(String),
+    SceneSaved,
+    SceneApplied(String),
+    SceneDiffed(String),
+    SleepDelayChanged(String),
+    SleepSinkToggled(bool),
+    SleepApplicationToggled(String, bool),
+    SleepPauseToggled(bool),
+    SleepStarted,
+    SleepExtended,
+    SleepCancelled,
+    Undo,
+    Redo,
+    EventOccurred(Event),
+    Tick,
 }
 
-impl Sandbox for UserInterface {
+impl Application for UserInterface {
+    type Executor = executor::Default;
     type Message = Message;
+    type Flags = Flags;
 
-    fn new() -> Self {
-	Self {
-	    pulse_handler:     PulseHandler::new().unwrap(),
+    fn new(flags: Flags) -> (Self, Command<Message>) {
+	let config       = flags.config;
+	let theme_source = ThemeSource::new(&config.theme);
+
+	let mut pulse_handler   = PulseHandler::new().unwrap();
+	let devices_changed     = Rc::new(Cell::new(false));
+	let devices_changed_ref = devices_changed.clone();
+	// Set at first, for the caps to be checked at the start.
+	let sinks_changed       = Rc::new(Cell::new(true));
+	let sinks_changed_ref   = sinks_changed.clone();
+	let source_outputs_changed     = Rc::new(Cell::new(true));
+	let source_outputs_changed_ref = source_outputs_changed.clone();
+	pulse_handler.subscribe(InterestMaskSet::CARD | InterestMaskSet::SINK | InterestMaskSet::SOURCE
+				| InterestMaskSet::SOURCE_OUTPUT,
+				move |facility, _operation, _index| {
+	    match facility {
+		Some(Facility::SourceOutput) => source_outputs_changed_ref.set(true),
+		Some(Facility::Sink)         => {
+		    devices_changed_ref.set(true);
+		    sinks_changed_ref.set(true);
+		}
+		_                            => devices_changed_ref.set(true),
+	    }
+	});
+	let modules = modules::restore(&mut pulse_handler);
+
+	(Self {
+	    pulse_handler,
 	    scroll:            scrollable::State::new(),
+	    requests:          flags.requests,
+	    focused:           None,
+	    is_hidden:         false,
+	    ptt:               PushToTalk::new(&config.ptt),
+	    ducking:           Ducking::new(&config.ducking),
+	    fader:             Fader::default(),
+	    fade_config:       config.fade,
+	    sleep:             None,
+	    sleep_pause:       None,
+	    sleep_config:      config.sleep,
+	    mpris:             Mpris::new(),
+
+	    theme:             theme_source.theme(),
+	    theme_source,
+	    theme_checked:     Instant::now(),
+
+	    layout:            config.layout,
+	    layout_buttons:    Default::default(),
+
+	    page:              Page::Streams,
+	    page_buttons:      Default::default(),
+	    devices:           DeviceState::default(),
+	    devices_changed,
+	    sinks_changed,
+	    source_outputs_changed,
+	    limits:            config.limits,
+	    limit_notice:      None,
+	    modules,
+	    scenes:            SceneState::default(),
+	    server:            ServerState::default(),
+	    sleep_ui:          SleepState::default(),
+	    history:           History::default(),
+	    history_ui:        HistoryState::default(),
 
 	    sink_input_uis:    Vec::new(),
 	    sink_input_datas:  Rc::new(RefCell::new(Vec::new())),
+	    source_outputs:    Vec::new(),
+	    streams:           config.streams,
+	    filter:            FilterState::default(),
+	    group_uis:         Vec::new(),
+	    expanded:          Vec::new(),
+	    scaling:           None,
+	    links:             Links::default(),
+	    link_ui:           LinkState::default(),
 
-	    sink_ui:           (slider::State::new(), button::State::new()),
+	    sink_ui:           RowState::default(),
 	    sink_data:         Rc::new(RefCell::new(MainData::default())),
 
-	    source_ui:         (slider::State::new(), button::State::new()),
+	    source_ui:         RowState::default(),
 	    source_data:       Rc::new(RefCell::new(MainData::default())),
-	}
+
+	    inspector:         InspectorState::default(),
+	}, Command::none())
     }
 
     fn title(&self) -> String {
 	String::from(APPLICATION_NAME)
     }
 
-    fn update(&mut self, message: Message) {
+    fn update(&mut self, message: Message) -> Command<Message> {
 	match message {
 	    Message::SliderChanged(index, id, volume) => {
-		#[cfg(debug_assertions)]
-		println!("Log: slider with index {} of {} changed to {}.", index, id, volume);
-		
-		self.sink_input_datas.borrow_mut()[index].volume = volume;
-		update_sink_input_volume_by_id(&mut self.pulse_handler, id, volume);
+		log::debug!("Slider with index {} of {} changed to {}", index, id, volume);
+
+		self.change(Target::SinkInput(id), Change::Volume(volume));
 	    }
 	    Message::MuteButtonPressed(id, status) => {
-		#[cfg(debug_assertions)]
-		println!("Log: button of {} pressed with status to {}.", id, status);
-		
-		update_sink_input_mute_by_id(&mut self.pulse_handler, id, status);
+		log::debug!("Button of {} pressed with status to {}", id, status);
+
+		self.change(Target::SinkInput(id), Change::Mute(status));
 	    }
 	    
 	    Message::SinkSliderChanged(volume) => {
-		#[cfg(debug_assertions)]
-		println!("Log: volume slider changed to {}.", volume);
+		log::debug!("Volume slider changed to {}", volume);
 
-		update_sink_volume(&mut self.pulse_handler, volume);
+		self.change(Target::Sink, Change::Volume(volume));
 	    }
 	    Message::SinkMuteButtonPressed(status) => {
-		#[cfg(debug_assertions)]
-		println!("Log: volume button pressed with status to {}.", status);
+		log::debug!("Volume button pressed with status to {}", status);
 
-		update_sink_mute(&mut self.pulse_handler, status);
+		self.change(Target::Sink, Change::Mute(status));
 	    }
 	    
 	    Message::SourceSliderChanged(volume) => {
-		#[cfg(debug_assertions)]
-		println!("Log: volume slider changed to {}.", volume);
+		log::debug!("Volume slider changed to {}", volume);
 
-		update_source_volume(&mut self.pulse_handler, volume);
+		self.change(Target::Source, Change::Volume(volume));
 	    }
 	    Message::SourceMuteButtonPressed(status) => {
-		#[cfg(debug_assertions)]
-		println!("Log: volume button pressed with status to {}.", status);
+		log::debug!("Volume button pressed with status to {}", status);
+
+		self.change(Target::Source, Change::Mute(status));
+	    }
+
+	    Message::FadeOutPressed(target) => {
+		log::debug!("Fade out of {:?} pressed", target);
+
+		self.fade_out(target);
+	    }
+
+	    Message::SleepDelayChanged(delay) => {
+		self.sleep_ui.delay = delay;
+	    }
+	    Message::SleepSinkToggled(is_on) => {
+		self.sleep_ui.applications_only = !is_on;
+	    }
+	    Message::SleepApplicationToggled(name, is_on) => {
+		if let Some(application) = self.sleep_ui.applications.iter_mut().find(|a| a.name == name) {
+		    application.is_selected = is_on;
+		}
+		if is_on {
+		    self.sleep_ui.applications_only = true;
+		}
+	    }
+	    Message::SleepPauseToggled(is_on) => {
+		self.sleep_ui.pauses = is_on;
+	    }
+	    Message::SleepStarted => {
+		let target = if self.sleep_ui.applications_only {
+		    SleepTarget::Applications(self.sleep_ui.applications.iter()
+					      .filter(|application| application.is_selected)
+					      .map(|application| application.name.clone())
+					      .collect())
+		} else {
+		    SleepTarget::Sink
+		};
+
+		match sleep::parse_delay(&self.sleep_ui.delay) {
+		    Ok(delay) => {
+			log::debug!("Sleep timer of {:?} started for {:?}", target, delay);
+
+			self.sleep          = Some(SleepTimer::new(target, self.sleep_ui.pauses, delay));
+			self.sleep_ui.report = None;
+		    }
+		    Err(e)    => self.sleep_ui.report = Some(e),
+		}
+	    }
+	    Message::SleepExtended => {
+		if let Some(timer) = &mut self.sleep {
+		    timer.extend(Duration::from_secs(self.sleep_config.extension * 60));
+		}
+	    }
+	    Message::SleepCancelled => {
+		self.sleep       = None;
+		self.sleep_pause = None;
+	    }
+
+	    Message::PlayerAction(bus_name, action) => {
+		log::debug!("{:?} sent to {}", action, bus_name);
+
+		if let Some(mpris) = &self.mpris {
+		    mpris.send(&bus_name, action);
+		}
+	    }
+
+	    Message::Inspect(target) => {
+		log::debug!("Inspecting {:?}", target);
+
+		self.inspector.target = Some(target);
+		self.refresh_inspector();
+	    }
+	    Message::InspectorRefreshed => self.refresh_inspector(),
+	    Message::InspectorCopied => {
+		if let Some(details) = &self.inspector.details {
+		    self.inspector.report = Some(match clipboard::copy(&details.to_text()) {
+			Ok(())  => String::from("Copied to the clipboard"),
+			Err(e)  => e,
+		    });
+		}
+	    }
+	    Message::InspectorClosed => {
+		self.inspector = InspectorState::default();
+	    }
+	    Message::SearchChanged(search) => self.filter.search = search,
+	    Message::StreamHidden(name, is_hidden) => {
+		log::debug!("Streams of {} hidden: {}", name, is_hidden);
+
+		self.streams.set_hidden(&name, is_hidden);
+		self.save_streams();
+	    }
+	    Message::HideEventsToggled(is_on) => {
+		self.streams.hide_events = is_on;
+		self.save_streams();
+	    }
+	    Message::HideOwnToggled(is_on) => {
+		self.streams.hide_own = is_on;
+		self.save_streams();
+	    }
+	    Message::ShowHiddenToggled(is_on) => self.filter.show_hidden = is_on,
+	    Message::GroupingToggled(is_on) => {
+		self.streams.group = is_on;
+		self.save_streams();
+	    }
+	    Message::GroupSliderChanged(name, volume) => {
+		log::debug!("Slider of the group {} changed to {}", name, volume);
+
+		self.change_group_volume(&name, volume);
+	    }
+	    Message::GroupMuteButtonPressed(name, status) => {
+		log::debug!("Button of the group {} pressed with status to {}", name, status);
+
+		for (id, _) in self.group_members(&name) {
+		    self.change(Target::SinkInput(id), Change::Mute(status));
+		}
+	    }
+	    Message::GroupExpanded(name, is_expanded) => {
+		self.expanded.retain(|expanded| *expanded != name);
+		if is_expanded {
+		    self.expanded.push(name);
+		}
+	    }
+	    Message::LinkToggled(target, is_on) => {
+		if self.links.number(target).is_some() {
+		    log::debug!("Unlinking {:?}", target);
+
+		    self.links.unlink(target);
+		} else if is_on {
+		    self.link_ui.selected.push(target);
+		} else {
+		    self.link_ui.selected.retain(|&selected| selected != target);
+		}
+	    }
+	    Message::LinkCreated(mode) => {
+		let targets = std::mem::take(&mut self.link_ui.selected);
+		log::debug!("Linking {:?} in the {:?} mode", targets, mode);
+
+		self.links.add(targets, mode);
+	    }
+	    Message::LinkCancelled => self.link_ui.selected.clear(),
+	    Message::ServerRefreshed => self.refresh_server(),
 
-		update_source_mute(&mut self.pulse_handler, status);
+	    Message::Undo => self.undo(),
+	    Message::Redo => self.redo(),
+
+	    Message::LayoutSelected(layout) => {
+		self.layout = layout;
+	    }
+
+	    Message::PageSelected(page) => {
+		self.page = page;
+		match page {
+		    Page::Devices => self.refresh_devices(),
+		    Page::Scenes  => self.refresh_scenes(),
+		    Page::Server  => self.refresh_server(),
+		    _             => {}
+		}
+	    }
+	    Message::CardProfileSelected(index, profile) => {
+		log::debug!("Profile {} selected for card {}", profile, index);
+
+		update_card_profile(&mut self.pulse_handler, index, &profile);
+		self.refresh_devices();
+	    }
+	    Message::SinkPortSelected(index, port) => {
+		log::debug!("Port {} selected for sink {}", port, index);
+
+		update_sink_port(&mut self.pulse_handler, index, &port);
+		self.refresh_devices();
+	    }
+	    Message::SourcePortSelected(index, port) => {
+		log::debug!("Port {} selected for source {}", port, index);
+
+		update_source_port(&mut self.pulse_handler, index, &port);
+		self.refresh_devices();
+	    }
+	    Message::ModuleKindSelected(kind) => {
+		self.devices.modules.kind = kind;
+	    }
+	    Message::ModuleNameChanged(name) => {
+		self.devices.modules.name = name;
+	    }
+	    Message::ModuleSourceChanged(source) => {
+		self.devices.modules.source = source;
+	    }
+	    Message::ModuleSinkChanged(sink) => {
+		self.devices.modules.sink = sink;
+	    }
+	    Message::ModuleRecreateToggled(recreate) => {
+		self.devices.modules.recreate = recreate;
+	    }
+	    Message::ModuleCreated => {
+		let form  = &self.devices.modules;
+		let name  = form.name.trim().to_owned();
+		let setup = match form.kind {
+		    ModuleKind::NullSink    => Setup::NullSink { name },
+		    ModuleKind::Loopback    => Setup::Loopback {
+			source: form.source.trim().to_owned(),
+			sink:   form.sink.trim().to_owned(),
+		    },
+		    ModuleKind::CombineSink => Setup::CombineSink {
+			name,
+			sinks: form.sink.split(',').map(|sink| sink.trim().to_owned()).collect(),
+		    },
+		    ModuleKind::EchoCancel  => Setup::EchoCancel {
+			name,
+			source: form.source.trim().to_owned(),
+			sink:   form.sink.trim().to_owned(),
+		    },
+		};
+
+		log::debug!("Creating {:?}", setup);
+
+		let description = setup.describe();
+		let recreate    = form.recreate;
+		self.devices.modules.report = Some(
+		    match modules::create(&mut self.pulse_handler, &mut self.modules, setup, recreate) {
+			Ok(())  => format!("Created {}", description),
+			Err(e)  => e,
+		    });
+		self.refresh_devices();
+	    }
+	    Message::ModuleRemoved(position) => {
+		log::debug!("Removing module {:?}", self.modules.get(position));
+
+		self.devices.modules.report = match modules::remove(&mut self.pulse_handler,
+								    &mut self.modules,
+								    position) {
+		    Ok(())  => None,
+		    Err(e)  => Some(e),
+		};
+		self.refresh_devices();
+	    }
+	    Message::SceneNameChanged(name) => {
+		self.scenes.name = name;
+	    }
+	    Message::SceneSaved => {
+		let name = self.scenes.name.trim().to_owned();
+		self.scenes.report = match scene::save(&name, &Scene::capture(&mut self.pulse_handler)) {
+		    Ok(())   => vec![format!("Saved {}", name)],
+		    Err(e)   => vec![e],
+		};
+		self.refresh_scenes();
+	    }
+	    Message::SceneApplied(name) => {
+		self.scenes.report = match scene::load(&name) {
+		    Ok(scene) => std::iter::once(format!("Applied {}", name))
+			.chain(scene.apply(&mut self.pulse_handler, &mut self.fader, &self.fade_config)
+			       .into_iter()
+			       .map(|skipped| format!("Skipped {}", skipped)))
+			.collect(),
+		    Err(e)    => vec![e],
+		};
+	    }
+	    Message::SceneDiffed(name) => {
+		self.scenes.report = match scene::load(&name) {
+		    Ok(scene) => {
+			let differences = scene.diff(&Scene::capture(&mut self.pulse_handler));
+			if differences.is_empty() {
+			    vec![format!("{} matches the current setup", name)]
+			} else {
+			    differences
+			}
+		    }
+		    Err(e)    => vec![e],
+		};
+	    }
+
+	    Message::EventOccurred(Event::Window(window::Event::Focused)) => {
+		osd::set_main_focused(true);
+	    }
+	    Message::EventOccurred(Event::Window(window::Event::Unfocused)) => {
+		osd::set_main_focused(false);
+		// The release of a held key is not seen once the window lost the focus.
+		self.ptt.set_key_held(false);
+		self.apply_ptt();
+	    }
+	    Message::EventOccurred(Event::Keyboard(keyboard::Event::KeyPressed {
+		key_code: keyboard::KeyCode::Z,
+		modifiers,
+	    })) if modifiers.control && !self.is_typing() => {
+		if modifiers.shift {
+		    self.redo();
+		} else {
+		    self.undo();
+		}
+	    }
+	    Message::EventOccurred(Event::Keyboard(keyboard::Event::KeyPressed { key_code, .. }))
+		if self.ptt.is_key(&format!("{:?}", key_code)) =>
+	    {
+		self.ptt.set_key_held(true);
+		self.apply_ptt();
+	    }
+	    Message::EventOccurred(Event::Keyboard(keyboard::Event::KeyReleased { key_code, .. }))
+		if self.ptt.is_key(&format!("{:?}", key_code)) =>
+	    {
+		self.ptt.set_key_held(false);
+		self.apply_ptt();
 	    }
+	    Message::EventOccurred(_) => {}
+	    Message::Tick => {
+		for rejected in self.pulse_handler.poll() {
+		    self.rollback(rejected);
+		}
+		// Jack detection changes the availability of ports and profiles.
+		if self.devices_changed.replace(false) && self.page == Page::Devices {
+		    self.refresh_devices();
+		}
+
+		let requests: Vec<_> = self.requests.iter()
+		    .flat_map(|requests| requests.try_iter())
+		    .collect();
+		for request in requests {
+		    self.handle_request(request);
+		}
+		self.apply_ptt();
+		self.apply_ducking();
+		self.apply_sleep();
+		self.apply_fades();
+		self.apply_limits();
+
+		if self.theme_source.follows_desktop()
+		    && self.theme_checked.elapsed() >= THEME_INTERVAL
+		{
+		    self.theme = self.theme_source.theme();
+		    self.theme_checked = Instant::now();
+		}
+
+		if let Some(mpris) = &mut self.mpris {
+		    mpris.refresh();
+		}
+	    }
+	}
+
+	Command::none()
+    }
+
+    fn mode(&self) -> iced::window::Mode {
+	if self.is_hidden {
+	    iced::window::Mode::Hidden
+	} else {
+	    iced::window::Mode::Windowed
 	}
     }
 
+    fn subscription(&self) -> Subscription<Message> {
+	Subscription::batch(vec![
+	    time::every(POLL_INTERVAL).map(|_| Message::Tick),
+	    subscription::events().map(Message::EventOccurred),
+	])
+    }
+
     fn view(&mut self) -> Element<Message> {
 	self.update_data();
 
-	#[cfg(feature = "time")]
-	let start = SystemTime::now();
+	let start = Instant::now();
 
-	let mut scrollable = Scrollable::new(&mut self.scroll)
-            .width(Length::Fill)
-            .height(Length::Fill);
+	let layout     = self.layout;
+	let theme      = self.theme;
+	let channels   = self.channels();
+	let name_width = layout::name_width(channels.iter().map(|channel| channel.name.as_str()),
+					    layout.text_size(),
+					    layout.max_name_width());
 
-	{
-	    let data    = self.sink_data.borrow();
-	    let (is_mute, volume) = (data.mute, data.volume);
-	    
-    	    let text    = Self::create_name(SINK_NAME);
-    	    let slider  = Slider::new(&mut self.sink_ui.0,
-    				      0.0 ..= MAX_VOLUME_FLOAT,
-    				      volume as f32,
-    				      move |v| Message::SinkSliderChanged(v as u32));
-	    let button  = Button::new(&mut self.sink_ui.1,
-    				      Self::create_status_button(is_mute),
-    				      move || Message::SinkMuteButtonPressed(!is_mute))
-    		.width(Length::from(MUTE_BUTTON_SIZE))
-    		.padding(10);
-    	    let status  = Self::create_status(volume);	    
-    	    let row     = Row::new()
-    		.align_items(Align::Center)
-    		.spacing(10)
-    		.push(text)
-    		.push(slider)
-    		.push(status)
-    		.push(button);
-	    
-	    scrollable = scrollable.push(row);
-	}
+	let page   = self.page;
+	let header = self.page_buttons.iter_mut()
+	    .zip(Page::ALL.iter())
+	    .fold(Row::new().spacing(layout.spacing()).align_items(Align::Center),
+		  |row, (state, &option)| {
+		row.push(Button::new(state,
+				     Text::new(option.name()).size(layout.text_size()),
+				     move || Message::PageSelected(option))
+			 .disabled(option == page)
+			 .style(theme.button())
+			 .padding(layout.toggle_padding()))
+	    })
+	    .push(Space::with_width(Length::from(layout.spacing())));
+	let header = self.layout_buttons.iter_mut()
+	    .zip(Layout::ALL.iter())
+	    .fold(header, |row, (state, &option)| {
+		row.push(Button::new(state,
+				     Text::new(option.name()).size(layout.text_size()),
+				     move || Message::LayoutSelected(option))
+			 .disabled(option == layout)
+			 .style(theme.button())
+			 .padding(layout.toggle_padding()))
+	    })
+	    .push(Space::with_width(Length::Fill))
+	    .push(Self::create_limit_notice(&theme, layout, self.limit_notice.as_ref()))
+	    .push(Self::create_sleep_countdown(&theme, layout, self.sleep.as_ref()))
+	    .push(Self::create_mic_indicator(&theme,
+					     layout,
+					     self.source_data.borrow().mute,
+					     self.ptt.mode()));
 
-	{
-	    let data    = self.source_data.borrow();
-	    let (is_mute, volume) = (data.mute, data.volume);
-	    
-    	    let text    = UserInterface::create_name(SOURCE_NAME);
-    	    let slider  = Slider::new(&mut self.source_ui.0,
-    				      0.0 ..= MAX_VOLUME_FLOAT,
-    				      volume as f32,
-    				      move |v| Message::SourceSliderChanged(v as u32));
-	    let button  = Button::new(&mut self.source_ui.1,
-    				      Self::create_status_button(is_mute),
-    				      move || Message::SourceMuteButtonPressed(!is_mute))
-    		.width(Length::from(MUTE_BUTTON_SIZE))
-    		.padding(10);
-    	    let status  = Self::create_status(volume);
-    	    let row     = Row::new()
-    		.align_items(Align::Center)
-    		.spacing(10)
-    		.push(text)
-    		.push(slider)
-    		.push(status)
-    		.push(button);
-	    
-	    scrollable = scrollable.push(row);
-	}
+	let body: Element<Message> = match page {
+	    Page::Streams => {
+		let group_count = channels.iter()
+		    .filter(|channel| matches!(channel.slot, Slot::Group(_)))
+		    .count();
+		self.group_uis.resize_with(group_count, RowState::default);
+		let hidden_count = self.sink_input_datas.borrow().iter()
+		    .filter(|data| self.streams.is_hidden(data))
+		    .count();
+		let filter = Self::create_filter(&mut self.filter, &self.streams, hidden_count, &theme, layout);
 
-	let datas = self.sink_input_datas.borrow();
-	for (index, ui) in self.sink_input_uis.iter_mut().enumerate() {
-	    let (id, is_mute, volume) = (datas[index].id, datas[index].mute, datas[index].volume);
-
-    	    let text    = UserInterface::create_name(&datas[index].name);
-    	    let slider  = Slider::new(&mut ui.0,
-    				      0.0 ..= MAX_VOLUME_FLOAT,
-    				      volume as f32,
-    				      move |v| Message::SliderChanged(index, id, v as u32));
-    	    let button  = Button::new(&mut ui.1,
-    				      Self::create_status_button(is_mute),
-    				      move || Message::MuteButtonPressed(id, !is_mute))
-    		.width(Length::from(MUTE_BUTTON_SIZE))
-    		.padding(10);
-    	    let status  = Self::create_status(volume);
-    	    let row     = Row::new()
-    		.spacing(10)
-    		.align_items(Align::Center)
-    		.push(text)
-    		.push(slider)
-    		.push(status)
-    		.push(button);
-
-	    scrollable = scrollable.push(row);
-	}
+		// Every row takes its own states, in whatever order the rows come.
+		let mut sink_state    = Some(&mut self.sink_ui);
+		let mut source_state  = Some(&mut self.source_ui);
+		let mut stream_states: Vec<_> = self.sink_input_uis.iter_mut().map(Some).collect();
+		let mut group_states:  Vec<_> = self.group_uis.iter_mut().map(Some).collect();
+		let rows = channels.into_iter().filter_map(|channel| {
+		    let state = match channel.slot {
+			Slot::Sink            => sink_state.take(),
+			Slot::Source          => source_state.take(),
+			Slot::Stream(index)   => stream_states.get_mut(index).and_then(Option::take),
+			Slot::Group(position) => group_states.get_mut(position).and_then(Option::take),
+		    }?;
+		    Some(Self::create_channel(layout, &theme, name_width, channel, state))
+		});
+
+		let scrollable = Scrollable::new(&mut self.scroll)
+		    .width(Length::Fill)
+		    .height(Length::Fill)
+		    .spacing(layout.spacing());
+
+		let scrollable = match layout {
+		    Layout::Horizontal | Layout::Compact => rows.fold(scrollable, |scrollable, row| {
+			scrollable.push(row)
+		    }),
+		    Layout::Vertical => scrollable.push(rows.fold(Row::new().spacing(layout.spacing()),
+								  |columns, column| columns.push(column))),
+		};
+
+		let column = Column::new()
+		    .spacing(layout.spacing())
+		    .push(filter);
+		let column = if self.link_ui.selected.is_empty() {
+		    column
+		} else {
+		    column.push(Self::create_link_bar(&mut self.link_ui, &theme, layout))
+		};
+		if self.inspector.details.is_some() {
+		    column
+			.push(scrollable.height(Length::FillPortion(2)))
+			.push(Self::create_inspector(&mut self.inspector, &theme, layout))
+			.into()
+		} else {
+		    column.push(scrollable).into()
+		}
+	    }
+	    Page::Devices => Self::create_devices_page(&mut self.devices, &self.modules, &theme, layout),
+	    Page::Scenes  => Self::create_scenes_page(&mut self.scenes, &theme, layout),
+	    Page::Server  => Self::create_server_page(&mut self.server,
+						      &self.pulse_handler.stats,
+						      &theme,
+						      layout),
+	    Page::History => Self::create_history_page(&mut self.history_ui,
+						       &self.history,
+						       &theme,
+						       layout),
+	    Page::Sleep   => {
+		self.refresh_sleep_applications();
+		Self::create_sleep_page(&mut self.sleep_ui, self.sleep.as_ref(), &theme, layout)
+	    }
+	};
 
 	let content = Column::new()
-	    .spacing(20)
-	    .padding(20)
-	    .push(scrollable);
-
-	#[cfg(feature = "time")]
-	println!("Initialized for {} s.",
-		 SystemTime::now().duration_since(start).unwrap().as_secs_f64()
-	);
+	    .spacing(layout.spacing())
+	    .padding(layout.padding())
+	    .push(header)
+	    .push(body);
+
+	self.pulse_handler.stats.record("View build", start.elapsed());
 	
 	Container::new(content)
 	    .width(Length::Fill)
 	    .height(Length::Fill)
+	    .style(self.theme.container())
 	    .center_x()
 	    .center_y()
 	    .into()
@@ -231,8 +1084,7 @@ impl Sandbox for UserInterface {
 
 impl UserInterface {
     fn update_data(&mut self) {
-	#[cfg(debug_assertions)]
-	println!("Log: Updating.");
+	log::trace!("Updating");
 	
 	update_sink_inputs (&mut self.pulse_handler,
 			    self.sink_input_datas.clone(),
@@ -243,33 +1095,1564 @@ impl UserInterface {
 	
 	update_fetch_source(&mut self.pulse_handler,
 			    self.source_data.clone());
+
+	// The links and the selection forget the streams which ended.
+	let datas      = self.sink_input_datas.borrow();
+	let is_playing = |target: Target| match target {
+	    Target::SinkInput(id) => datas.iter().any(|data| data.id == id),
+	    _                     => true,
+	};
+	self.links.retain(&is_playing);
+	self.link_ui.selected.retain(|&target| is_playing(target));
     }
 
-    fn create_name(name: &str) -> Text {
-	Text::new(name.clone())
-    	    .width(Length::from(APPLICATION_NAME_SIZE))
-    	    .vertical_alignment(VerticalAlignment::Center)
-    	    .horizontal_alignment(HorizontalAlignment::Right)
+    fn channels(&self) -> Vec<Channel> {
+	let sink    = self.sink_data.borrow();
+	let source  = self.source_data.borrow();
+	let pending = &self.pulse_handler.pending;
+
+	let mut channels = vec![
+	    Channel {
+		name:       String::from(SINK_NAME),
+		volume:     sink.volume,
+		is_mute:    sink.mute,
+		is_pending: pending.is_pending(Target::Sink),
+		is_focused: false,
+		is_locked:  false,
+		is_fading:  self.fader.is_fading(Target::Sink),
+		is_hidden:  false,
+		application: None,
+		expanded:   None,
+		is_selected: self.link_ui.selected.contains(&Target::Sink),
+		link:       self.links.number(Target::Sink),
+		max_volume: slider_max(self.limits.sink(&sink.name)),
+		slot:       Slot::Sink,
+		target:     Target::Sink,
+		player:     None,
+		on_slide:   Box::new(Message::SinkSliderChanged),
+		on_toggle:  Box::new(Message::SinkMuteButtonPressed),
+	    },
+	    Channel {
+		name:       String::from(SOURCE_NAME),
+		volume:     source.volume,
+		is_mute:    source.mute,
+		is_pending: pending.is_pending(Target::Source),
+		is_focused: false,
+		is_locked:  self.ptt.mode() != PttMode::Off,
+		is_fading:  self.fader.is_fading(Target::Source),
+		is_hidden:  false,
+		application: None,
+		expanded:   None,
+		is_selected: self.link_ui.selected.contains(&Target::Source),
+		link:       self.links.number(Target::Source),
+		max_volume: MAX_VOLUME,
+		slot:       Slot::Source,
+		target:     Target::Source,
+		player:     None,
+		on_slide:   Box::new(Message::SourceSliderChanged),
+		on_toggle:  Box::new(Message::SourceMuteButtonPressed),
+	    },
+	];
+
+	let datas = self.sink_input_datas.borrow();
+	let shown = datas.iter()
+	    .enumerate()
+	    .filter(|(_, data)| self.is_shown(data));
+	if !self.streams.group {
+	    channels.extend(shown.map(|(index, data)| self.stream_channel(index, data, data.name.clone())));
+	    return channels;
+	}
+
+	for (position, group) in group::by_application(shown).into_iter().enumerate() {
+	    if group.members.len() == 1 {
+		let index = group.members[0];
+		channels.push(self.stream_channel(index, &datas[index], group.name));
+		continue;
+	    }
+
+	    let is_expanded = self.expanded.contains(&group.name);
+	    channels.push(self.group_channel(position, &group, is_expanded));
+	    if is_expanded {
+		channels.extend(group.members.iter().map(|&index| {
+		    let data  = &datas[index];
+		    let title = data.title.as_ref().unwrap_or(&data.name);
+		    self.stream_channel(index, data, format!("{} {}", MEMBER_MARK, title))
+		}));
+	    }
+	}
+
+	channels
+    }
+
+    fn stream_channel(&self, index: usize, data: &SinkInputData, name: String) -> Channel {
+	let id = data.id;
+
+	Channel {
+	    name,
+	    volume:      data.volume,
+	    is_mute:     data.mute,
+	    is_pending:  self.pulse_handler.pending.is_pending(Target::SinkInput(id)),
+	    is_focused:  self.is_focused(&data.name),
+	    is_locked:   false,
+	    is_fading:   self.fader.is_fading(Target::SinkInput(id)),
+	    is_hidden:   self.streams.hidden.contains(&data.name),
+	    application: Some(data.name.clone()),
+	    expanded:    None,
+	    is_selected: self.link_ui.selected.contains(&Target::SinkInput(id)),
+	    link:        self.links.number(Target::SinkInput(id)),
+	    max_volume:  slider_max(self.limits.application(&data.name)),
+	    slot:        Slot::Stream(index),
+	    target:      Target::SinkInput(id),
+	    player:      self.mpris.as_ref().and_then(|mpris| mpris.find(data)).cloned(),
+	    on_slide:    Box::new(move |volume| Message::SliderChanged(index, id, volume)),
+	    on_toggle:   Box::new(move |status| Message::MuteButtonPressed(id, status)),
+	}
+    }
+
+    /// The row of an application with several streams: the loudest stream
+    /// gives its volume, and it is muted when all of them are.
+    fn group_channel(&self, position: usize, group: &Group, is_expanded: bool) -> Channel {
+	let datas   = self.sink_input_datas.borrow();
+	let datas   = &*datas;
+	let members = move || group.members.iter().map(move |&index| &datas[index]);
+	let first   = &datas[group.members[0]];
+	let pending = &self.pulse_handler.pending;
+	let (slide_name, toggle_name) = (group.name.clone(), group.name.clone());
+
+	Channel {
+	    name:        format!("{} ({})", group.name, group.members.len()),
+	    volume:      members().map(|data| data.volume).max().unwrap_or(0),
+	    is_mute:     members().all(|data| data.mute),
+	    is_pending:  members().any(|data| pending.is_pending(Target::SinkInput(data.id))),
+	    is_focused:  self.is_focused(&group.name),
+	    is_locked:   false,
+	    is_fading:   members().any(|data| self.fader.is_fading(Target::SinkInput(data.id))),
+	    is_hidden:   self.streams.hidden.contains(&group.name),
+	    application: Some(group.name.clone()),
+	    expanded:    Some(is_expanded),
+	    is_selected: false,
+	    link:        None,
+	    max_volume:  slider_max(self.limits.application(&group.name)),
+	    slot:        Slot::Group(position),
+	    target:      Target::SinkInput(first.id),
+	    player:      self.mpris.as_ref().and_then(|mpris| mpris.find(first)).cloned(),
+	    on_slide:    Box::new(move |volume| Message::GroupSliderChanged(slide_name.clone(), volume)),
+	    on_toggle:   Box::new(move |status| Message::GroupMuteButtonPressed(toggle_name.clone(), status)),
+	}
+    }
+
+    /// The id and volume of the shown streams of an application.
+    fn group_members(&self, name: &str) -> Vec<(u32, u32)> {
+	self.sink_input_datas.borrow().iter()
+	    .filter(|data| data.name == name && self.is_shown(data))
+	    .map(|data| (data.id, data.volume))
+	    .collect()
+    }
+
+    /// Moves the streams of a group so that the loudest gets `volume`,
+    /// keeping their balance.
+    fn change_group_volume(&mut self, name: &str, volume: u32) {
+	let mut scaling = match self.scaling.take() {
+	    Some(scaling) if scaling.continues(name) => scaling,
+	    _                                        => Scaling::new(name, self.group_members(name)),
+	};
+
+	for (id, volume) in scaling.scale(volume) {
+	    self.change(Target::SinkInput(id), Change::Volume(volume));
+	}
+	self.scaling = Some(scaling);
+    }
+
+    /// Whether the stream has a row, given the search and the hidden
+    /// applications.
+    fn is_shown(&self, data: &SinkInputData) -> bool {
+	filter::matches(&self.filter.search, data)
+	    && (self.filter.show_hidden || !self.streams.is_hidden(data))
+    }
+
+    fn save_streams(&self) {
+	if let Err(e) = self.streams.save() {
+	    log::error!("{}", e);
+	}
+    }
+
+    fn create_channel<'a>(layout:     Layout,
+			  theme:      &Theme,
+			  name_width: u16,
+			  channel:    Channel,
+			  state:      &'a mut RowState) -> Element<'a, Message> {
+	let size     = layout.text_size();
+	let on_slide = channel.on_slide;
+	let target   = channel.target;
+	let mut name = Text::new(layout::elide(&channel.name, name_width, size))
+	    .size(size)
+	    .vertical_alignment(VerticalAlignment::Center);
+	if channel.is_focused {
+	    name = name.color(theme.palette.accent);
+	} else if channel.is_hidden {
+	    name = name.color(theme.palette.border);
+	}
+	let status   = Self::create_status(channel.volume, channel.is_pending)
+	    .size(size)
+	    .width(Length::from(layout.status_width()));
+	let toggle   = Self::create_mute_toggle(&mut state.mute,
+						theme,
+						layout,
+						channel.is_mute,
+						channel.is_pending || channel.is_locked,
+						channel.on_toggle);
+	// A group row expands instead of fading or inspecting a stream.
+	let mut actions: Vec<Element<'a, Message>> = match (channel.expanded, &channel.application) {
+	    (Some(is_expanded), Some(application)) => vec![
+		Self::create_expand_button(&mut state.expand, theme, layout, application.clone(), is_expanded),
+	    ],
+	    _                                      => vec![
+		Self::create_fade_button(&mut state.fade,
+					 theme,
+					 layout,
+					 channel.is_fading,
+					 channel.is_pending || channel.is_mute,
+					 target),
+		Button::new(&mut state.inspect,
+			    Text::new(match layout {
+				Layout::Horizontal                 => INSPECT_LABEL,
+				Layout::Compact | Layout::Vertical => INSPECT_ICON,
+			    }).size(size),
+			    move || Message::Inspect(target))
+		    .style(theme.button())
+		    .padding(layout.toggle_padding())
+		    .into(),
+	    ],
+	};
+	if channel.expanded.is_none() {
+	    actions.push(Self::create_link_toggle(&mut state.link,
+						  theme,
+						  layout,
+						  channel.is_selected,
+						  channel.link,
+						  target));
+	}
+	if let Some(application) = channel.application {
+	    actions.push(Self::create_hide_button(&mut state.hide, theme, layout, application, channel.is_hidden));
+	}
+	let player   = channel.player;
+	let buttons  = match &player {
+	    Some(player) => Some(Self::create_transport(&mut state.previous,
+							&mut state.play_pause,
+							&mut state.next,
+							theme,
+							layout,
+							player)),
+	    None         => None,
+	};
+
+	match layout {
+	    Layout::Horizontal | Layout::Compact => {
+		let slider = Slider::new(&mut state.slider,
+					 0.0 ..= channel.max_volume as f32,
+					 channel.volume as f32,
+					 move |v| on_slide(v as u32))
+		    .style(theme.slider());
+
+		let row = Row::new()
+		    .align_items(Align::Center)
+		    .spacing(layout.spacing())
+		    .push(name
+			  .width(Length::from(name_width))
+			  .horizontal_alignment(HorizontalAlignment::Right))
+		    .push(slider)
+		    .push(status)
+		    .push(toggle);
+		let row = actions.into_iter().fold(row, |row, action| row.push(action));
+
+		match (player, buttons) {
+		    (Some(player), Some(buttons)) => Column::new()
+			.spacing(layout.spacing() / 2)
+			.push(row)
+			.push(Row::new()
+			      .align_items(Align::Center)
+			      .spacing(layout.spacing())
+			      .push(Space::with_width(Length::from(name_width)))
+			      .push(Text::new(player.now_playing())
+				    .size(size)
+				    .width(Length::Fill)
+				    .color(theme.palette.muted))
+			      .push(buttons))
+			.into(),
+		    _                             => row.into(),
+		}
+	    }
+	    Layout::Vertical => {
+		let slider = VerticalSlider::new(&mut state.vertical_slider,
+						 0.0 ..= channel.max_volume as f32,
+						 channel.volume as f32,
+						 move |v| on_slide(v as u32))
+		    .height(Length::from(layout::VERTICAL_SLIDER_HEIGHT))
+		    .style(theme.slider());
+
+		let column = Column::new()
+		    .align_items(Align::Center)
+		    .spacing(layout.spacing())
+		    .width(Length::from(layout::COLUMN_WIDTH))
+		    .push(status)
+		    .push(slider)
+		    .push(toggle);
+		let column = actions.into_iter()
+		    .fold(column, |column, action| column.push(action))
+		    .push(name.horizontal_alignment(HorizontalAlignment::Center));
+
+		match (player, buttons) {
+		    (Some(player), Some(buttons)) => column
+			.push(Text::new(layout::elide(&player.now_playing(), layout::COLUMN_WIDTH, size))
+			      .size(size)
+			      .color(theme.palette.muted)
+			      .horizontal_alignment(HorizontalAlignment::Center))
+			.push(buttons)
+			.into(),
+		    _                             => column.into(),
+		}
+	    }
+	}
+    }
+
+    /// Makes a change asked by the user, recording it in the history.
+    ///
+    /// The change stops the fade of the target, and follows to the rows
+    /// linked to it.
+    fn change(&mut self, target: Target, change: Change) {
+	let previous = match self.change_one(target, change) {
+	    Some(previous) => previous,
+	    None           => return,
+	};
+
+	let follows = self.links.follow(target, previous, change, MAX_VOLUME, |other| self.volume(other));
+	for (other, change) in follows {
+	    self.change_one(other, change);
+	}
+    }
+
+    /// Makes the change of a single row, returning the value it replaces
+    /// when it differs.
+    fn change_one(&mut self, target: Target, change: Change) -> Option<Change> {
+	self.fader.cancel(target);
+
+	let change = self.limit(target, change);
+
+	let previous = match self.set(target, change) {
+	    Some(previous) if previous != change => previous,
+	    _                                    => return None,
+	};
+
+	let label = match target {
+	    Target::SinkInput(id) => self.sink_input_datas.borrow().iter()
+		.find(|data| data.id == id)
+		.map_or_else(String::new, |data| data.name.clone()),
+	    Target::Sink          => String::from(SINK_NAME),
+	    Target::Source        => String::from(SOURCE_NAME),
+	};
+	self.history.record(target, label, previous, change);
+	Some(previous)
     }
 
-    fn create_status(volume: u32) -> Text {
-	Text::new(&format!("{}%", volume * 100 / MAX_VOLUME))
+    fn volume(&self, target: Target) -> Option<u32> {
+	match target {
+	    Target::SinkInput(id) => self.sink_input_datas.borrow().iter()
+		.find(|data| data.id == id)
+		.map(|data| data.volume),
+	    Target::Sink          => Some(self.sink_data.borrow().volume),
+	    Target::Source        => Some(self.source_data.borrow().volume),
+	}
+    }
+
+    /// Whether a text input has the focus, which keeps the shortcuts for
+    /// its own editing.
+    fn is_typing(&self) -> bool {
+	let form = &self.devices.modules;
+
+	[&self.filter.search_input,
+	 &self.scenes.name_input,
+	 &self.sleep_ui.delay_input,
+	 &form.name_input,
+	 &form.source_input,
+	 &form.sink_input]
+	    .iter()
+	    .any(|input| input.is_focused())
+    }
+
+    fn undo(&mut self) {
+	if let Some(entry) = self.history.undo() {
+	    log::debug!("Undoing {}", entry.describe());
+
+	    self.fader.cancel(entry.target);
+	    self.set(entry.target, entry.before);
+	}
+    }
+
+    fn redo(&mut self) {
+	if let Some(entry) = self.history.redo() {
+	    log::debug!("Redoing {}", entry.describe());
+
+	    self.fader.cancel(entry.target);
+	    self.set(entry.target, entry.after);
+	}
+    }
+
+    /// Shows `change` right away and sends it to the server. Returns the
+    /// value it replaces, or `None` when the target is gone.
+    fn set(&mut self, target: Target, change: Change) -> Option<Change> {
+	let change   = self.limit(target, change);
+	let previous = match target {
+	    Target::SinkInput(id) => {
+		let mut datas = self.sink_input_datas.borrow_mut();
+		let data      = datas.iter_mut().find(|data| data.id == id)?;
+		replace_change(&mut data.volume, &mut data.mute, change)
+	    }
+	    Target::Sink          => {
+		let mut sink = self.sink_data.borrow_mut();
+		let sink     = &mut *sink;
+		replace_change(&mut sink.volume, &mut sink.mute, change)
+	    }
+	    Target::Source        => {
+		let mut source = self.source_data.borrow_mut();
+		let source     = &mut *source;
+		replace_change(&mut source.volume, &mut source.mute, change)
+	    }
+	};
+
+	let handler = &mut self.pulse_handler;
+	match (target, change, previous) {
+	    (Target::SinkInput(id), Change::Volume(volume), Change::Volume(previous)) =>
+		update_sink_input_volume_by_id(handler, id, volume, previous),
+	    (Target::SinkInput(id), Change::Mute(mute), Change::Mute(previous))     =>
+		update_sink_input_mute_by_id(handler, id, mute, previous),
+	    (Target::Sink, Change::Volume(volume), Change::Volume(previous))        =>
+		update_sink_volume(handler, volume, previous),
+	    (Target::Sink, Change::Mute(mute), Change::Mute(previous))              =>
+		update_sink_mute(handler, mute, previous),
+	    (Target::Source, Change::Volume(volume), Change::Volume(previous))      =>
+		update_source_volume(handler, volume, previous),
+	    (Target::Source, Change::Mute(mute), Change::Mute(previous))            =>
+		update_source_mute(handler, mute, previous),
+	    _                                                                        => {}
+	}
+
+	Some(previous)
+    }
+
+    /// Fetches the details of the inspected row again, closing the pane
+    /// once it is gone.
+    fn refresh_inspector(&mut self) {
+	let details = self.inspector.target
+	    .and_then(|target| fetch_details(&mut self.pulse_handler, target));
+
+	self.inspector.report = None;
+	match details {
+	    Some(details) => self.inspector.details = Some(details),
+	    None          => self.inspector = InspectorState::default(),
+	}
+    }
+
+    fn refresh_server(&mut self) {
+	let handler = &mut self.pulse_handler;
+
+	self.server.info   = Some(fetch_server_info(handler));
+	self.server.counts = vec![
+	    ("Clients",           fetch_client_count(handler)),
+	    ("Modules",           fetch_modules(handler).len()),
+	    ("Cards",             fetch_cards(handler).len()),
+	    ("Sinks",             fetch_sinks(handler).len()),
+	    ("Sources",           fetch_sources(handler).len()),
+	    ("Playing streams",   fetch_sink_inputs(handler).len()),
+	    ("Recording streams", fetch_source_outputs(handler).len()),
+	];
+    }
+
+    fn refresh_devices(&mut self) {
+	let handler = &mut self.pulse_handler;
+	let ports   = |device: DeviceData| PortEntry {
+	    ports: device.ports.iter().map(|_| button::State::new()).collect(),
+	    device,
+	};
+
+	self.devices.cards   = fetch_cards(handler).into_iter()
+	    .map(|card| CardEntry {
+		profiles: card.profiles.iter().map(|_| button::State::new()).collect(),
+		card,
+	    })
+	    .collect();
+	self.devices.sinks   = fetch_sinks(handler).into_iter().map(ports).collect();
+	self.devices.sources = fetch_sources(handler).into_iter().map(ports).collect();
+	self.devices.modules.remove.resize_with(self.modules.len(), button::State::new);
+    }
+
+    fn refresh_scenes(&mut self) {
+	self.scenes.entries = scene::list().into_iter()
+	    .map(|name| SceneEntry {
+		name,
+		apply: button::State::new(),
+		diff:  button::State::new(),
+	    })
+	    .collect();
+    }
+
+    fn handle_request(&mut self, request: Request) {
+	log::debug!("Launch request {:?}", request);
+
+	// Hiding only takes the window away: the engines, the D-Bus service and
+	// the instance socket keep running.
+	match request {
+	    Request::Show                => self.is_hidden = false,
+	    Request::Toggle              => self.is_hidden = !self.is_hidden,
+	    Request::Focus(application)  => {
+		self.is_hidden = false;
+		self.focused   = Some(application.to_lowercase());
+	    }
+	    Request::Talk(is_pressed)    => self.ptt.set_remote_held(is_pressed),
+	}
+    }
+
+    /// Whether this is the window owning the instance socket and the D-Bus
+    /// service. Only that one runs the engines acting on their own, which a
+    /// popup would otherwise fight against.
+    fn is_primary(&self) -> bool {
+	self.requests.is_some()
+    }
+
+    /// Mutes or unmutes the microphone as the push-to-talk triggers want.
+    fn apply_ptt(&mut self) {
+	if !self.is_primary() {
+	    return;
+	}
+
+	let mute = match self.ptt.mute() {
+	    Some(mute) => mute,
+	    None       => return,
+	};
+	if self.source_data.borrow().mute == mute
+	    || self.pulse_handler.pending.is_pending(Target::Source)
+	{
+	    return;
+	}
+
+	log::debug!("Push-to-talk sets the microphone mute to {}", mute);
+
+	let previous = std::mem::replace(&mut self.source_data.borrow_mut().mute, mute);
+	update_source_mute(&mut self.pulse_handler, mute, previous);
+    }
+
+    /// Lowers or restores the streams as the ducking engine wants.
+    fn apply_ducking(&mut self) {
+	if !self.is_primary() || !self.ducking.is_enabled() {
+	    return;
+	}
+
+	// Only fetched again when the subscription saw a recording stream
+	// come, go or change its properties.
+	if self.source_outputs_changed.replace(false) {
+	    self.source_outputs = fetch_source_outputs(&mut self.pulse_handler);
+	}
+	let pending        = &self.pulse_handler.pending;
+	let fader          = &self.fader;
+	let ducks          = self.ducking.update(&self.sink_input_datas.borrow(),
+					       &self.source_outputs,
+					       |id| pending.is_pending(Target::SinkInput(id))
+						   || fader.is_fading(Target::SinkInput(id)));
+
+	for duck in ducks {
+	    self.fader.start(Target::SinkInput(duck.id),
+			     duck.from,
+			     duck.to,
+			     self.ducking.ramp(),
+			     self.fade_config.curve);
+	}
+    }
+
+    /// Fades out what the sleep timer silences once it ran out, then pauses
+    /// its media players.
+    fn apply_sleep(&mut self) {
+	match (self.sleep_pause.take(), &self.mpris) {
+	    (Some((at, bus_names)), Some(mpris)) if Instant::now() >= at => {
+		for bus_name in bus_names {
+		    log::debug!("Sleep timer pauses {}", bus_name);
+		    mpris.send(&bus_name, mpris::Action::Pause);
+		}
+	    }
+	    (pause, _) => self.sleep_pause = pause,
+	}
+
+	let timer = match self.sleep.take() {
+	    Some(timer) if timer.is_due() => timer,
+	    timer                         => {
+		self.sleep = timer;
+		return;
+	    }
+	};
+
+	log::debug!("Sleep timer ran out, fading out {:?}", timer.target());
+
+	let fade    = Duration::from_millis(self.sleep_config.fade);
+	let curve   = self.fade_config.curve;
+	let targets = match timer.target() {
+	    SleepTarget::Sink                 => vec![(Target::Sink, self.sink_data.borrow().volume)],
+	    SleepTarget::Applications(names) => self.sink_input_datas.borrow().iter()
+		.filter(|data| names.contains(&data.name))
+		.map(|data| (Target::SinkInput(data.id), data.volume))
+		.collect(),
+	};
+	for (target, volume) in targets {
+	    self.fader.fade_out(target, volume, fade, curve);
+	}
+
+	if let (true, Some(mpris)) = (timer.pauses(), &self.mpris) {
+	    let playing = |player: &&Player| player.status == PlaybackStatus::Playing;
+	    let mut bus_names: Vec<String> = match timer.target() {
+		SleepTarget::Sink                 => mpris.players().iter()
+		    .filter(playing)
+		    .map(|player| player.bus_name.clone())
+		    .collect(),
+		SleepTarget::Applications(names) => self.sink_input_datas.borrow().iter()
+		    .filter(|data| names.contains(&data.name))
+		    .filter_map(|data| mpris.find(data))
+		    .filter(playing)
+		    .map(|player| player.bus_name.clone())
+		    .collect(),
+	    };
+	    bus_names.sort();
+	    bus_names.dedup();
+	    self.sleep_pause = Some((Instant::now() + fade, bus_names));
+	}
+    }
+
+    /// Lists the running applications on the sleep page, keeping their
+    /// selection.
+    fn refresh_sleep_applications(&mut self) {
+	let mut names: Vec<String> = self.sink_input_datas.borrow().iter()
+	    .map(|data| data.name.clone())
+	    .collect();
+	names.sort();
+	names.dedup();
+
+	let mut previous = std::mem::replace(&mut self.sleep_ui.applications, Vec::new());
+	self.sleep_ui.applications = names.into_iter()
+	    .map(|name| match previous.iter().position(|application| application.name == name) {
+		Some(index) => previous.swap_remove(index),
+		None        => SleepApplication {
+		    name,
+		    toggle:      button::State::new(),
+		    is_selected: false,
+		},
+	    })
+	    .collect();
+    }
+
+    /// Keeps a volume under the cap of its target.
+    fn limit(&self, target: Target, change: Change) -> Change {
+	match (change, self.cap(target)) {
+	    (Change::Volume(volume), Some(cap)) => Change::Volume(volume.min(cap)),
+	    _                                   => change,
+	}
+    }
+
+    fn cap(&self, target: Target) -> Option<u32> {
+	match target {
+	    Target::SinkInput(id) => self.sink_input_datas.borrow().iter()
+		.find(|data| data.id == id)
+		.and_then(|data| self.limits.application(&data.name)),
+	    Target::Sink          => self.limits.sink(&self.sink_data.borrow().name),
+	    Target::Source        => None,
+	}
+    }
+
+    /// Brings back under their caps the volumes other programs raised.
+    fn apply_limits(&mut self) {
+	let pending = &self.pulse_handler.pending;
+	let limits  = &self.limits;
+	let over: Vec<(u32, String, u32)> = self.sink_input_datas.borrow().iter()
+	    .filter(|data| !pending.is_pending(Target::SinkInput(data.id)))
+	    .filter_map(|data| {
+		let cap = limits.application(&data.name)?;
+		if data.volume > cap { Some((data.id, data.name.clone(), cap)) } else { None }
+	    })
+	    .collect();
+	for (id, name, cap) in over {
+	    self.set(Target::SinkInput(id), Change::Volume(cap));
+	    self.report_limit(&name, cap);
+	}
+
+	if self.sinks_changed.replace(false) {
+	    for sink in fetch_sinks(&mut self.pulse_handler) {
+		match self.limits.sink(&sink.name) {
+		    Some(cap) if sink.volume > cap => {
+			if update_sink_volume_by_index(&mut self.pulse_handler, sink.index, cap) {
+			    self.report_limit(&sink.description, cap);
+			} else {
+			    log::error!("Unable to cap the volume of {}", sink.name);
+			}
+		    }
+		    _                              => {}
+		}
+	    }
+	}
+    }
+
+    fn report_limit(&mut self, name: &str, cap: u32) {
+	let notice = format!("{} capped at {}%", name, cap * 100 / MAX_VOLUME);
+	log::warn!("{}", notice);
+
+	self.limit_notice = Some((notice, Instant::now()));
+    }
+
+    /// Sends the next volumes of the fades.
+    fn apply_fades(&mut self) {
+	for step in self.fader.step() {
+	    if let Some(mute) = step.mute {
+		self.set(step.target, Change::Mute(mute));
+	    }
+	    self.set(step.target, Change::Volume(step.volume));
+	}
+    }
+
+    /// Fades the target out and mutes it, or stops its fade.
+    fn fade_out(&mut self, target: Target) {
+	if self.fader.is_fading(target) {
+	    self.fader.cancel(target);
+	    return;
+	}
+
+	let volume = match target {
+	    Target::SinkInput(id) => match self.sink_input_datas.borrow().iter().find(|data| data.id == id) {
+		Some(data) => data.volume,
+		None       => return,
+	    },
+	    Target::Sink          => self.sink_data.borrow().volume,
+	    Target::Source        => self.source_data.borrow().volume,
+	};
+	self.fader.fade_out(target,
+			    volume,
+			    Duration::from_millis(self.fade_config.fade_out),
+			    self.fade_config.curve);
+    }
+
+    /// Returns `true` when the stream named `name` belongs to the focused
+    /// application.
+    fn is_focused(&self, name: &str) -> bool {
+	self.focused.as_ref()
+	    .map_or(false, |focused| name.to_lowercase().contains(focused.as_str()))
+    }
+
+    fn rollback(&mut self, rejected: Rejected) {
+	log::debug!("{:?} rejected, rolling back to {:?}", rejected.target, rejected.previous);
+
+	let mut datas  = self.sink_input_datas.borrow_mut();
+	let mut sink   = self.sink_data.borrow_mut();
+	let mut source = self.source_data.borrow_mut();
+
+	let (volume, mute) = match rejected.target {
+	    Target::SinkInput(id) => match datas.iter_mut().find(|data| data.id == id) {
+		Some(data) => (&mut data.volume, &mut data.mute),
+		None       => return,
+	    },
+	    Target::Sink   => {
+		let sink = &mut *sink;
+		(&mut sink.volume, &mut sink.mute)
+	    }
+	    Target::Source => {
+		let source = &mut *source;
+		(&mut source.volume, &mut source.mute)
+	    }
+	};
+
+	match rejected.previous {
+	    Change::Volume(previous) => *volume = previous,
+	    Change::Mute(previous)   => *mute = previous,
+	}
+    }
+
+    fn create_status(volume: u32, is_pending: bool) -> Text {
+	let pending = if is_pending { PENDING_MARK } else { "" };
+	Text::new(&format!("{}%{}", volume * 100 / MAX_VOLUME, pending))
     	    .horizontal_alignment(HorizontalAlignment::Center)
     	    .vertical_alignment(VerticalAlignment::Center)
-    	    .width(Length::from(PROCENT_STATUS_SIZE))
     }
 
-    fn create_status_button(is_mute: bool) -> Text {
-	if is_mute {
-	    Text::new("Mute")
+    fn create_server_page<'a>(server: &'a mut ServerState,
+			      stats:  &Stats,
+			      theme:  &Theme,
+			      layout: Layout) -> Element<'a, Message> {
+	let size    = layout.text_size();
+	let heading = |title: &str| Text::new(title).size(size).color(theme.palette.accent);
+	let line    = |label: &str, value: String| {
+	    Row::new()
+		.spacing(layout.spacing())
+		.push(Text::new(label).size(size).color(theme.palette.muted).width(Length::FillPortion(1)))
+		.push(Text::new(value).size(size).width(Length::FillPortion(2)))
+	};
+	let millis  = |duration: Duration| format!("{:.2} ms", duration.as_secs_f64() * 1000.0);
+
+	let mut column = Column::new()
+	    .spacing(layout.spacing() / 2)
+	    .push(Row::new()
+		  .align_items(Align::Center)
+		  .push(heading("Server").width(Length::Fill))
+		  .push(Button::new(&mut server.refresh,
+				    Text::new("Refresh").size(size),
+				    || Message::ServerRefreshed)
+			.style(theme.button())
+			.padding(layout.toggle_padding())));
+
+	if let Some(info) = &server.info {
+	    column = column
+		.push(line("Name",           info.server_name.clone()))
+		.push(line("Version",        info.server_version.clone()))
+		.push(line("Host",           format!("{}@{}", info.user_name, info.host_name)))
+		.push(line("Sample spec",    info.sample_spec.clone()))
+		.push(line("Channel map",    info.channel_map.clone()))
+		.push(line("Default sink",   info.default_sink.clone()))
+		.push(line("Default source", info.default_source.clone()));
+	}
+	if let Some(round_trip) = stats.get(ROUND_TRIP) {
+	    column = column.push(line("Latency", format!("{} on average, {} at most",
+							  millis(round_trip.average()),
+							  millis(round_trip.max))));
+	}
+
+	column = column.push(heading("Objects"));
+	for (label, count) in &server.counts {
+	    column = column.push(line(label, count.to_string()));
+	}
+
+	column = column.push(heading("Timings"));
+	for (label, timing) in stats.timings() {
+	    column = column.push(line(label, format!("{} calls, last {}, average {}, max {}",
+						     timing.count,
+						     millis(timing.last),
+						     millis(timing.average()),
+						     millis(timing.max))));
+	}
+
+	Scrollable::new(&mut server.scroll)
+	    .width(Length::Fill)
+	    .height(Length::Fill)
+	    .push(column)
+	    .into()
+    }
+
+    fn create_inspector<'a>(inspector: &'a mut InspectorState,
+			    theme:     &Theme,
+			    layout:    Layout) -> Element<'a, Message> {
+	let size    = layout.text_size();
+	let padding = layout.toggle_padding();
+	let details = match &inspector.details {
+	    Some(details) => details,
+	    None          => return Space::with_height(Length::Shrink).into(),
+	};
+
+	let mut header = Row::new()
+	    .spacing(layout.spacing())
+	    .align_items(Align::Center)
+	    .push(Text::new(&details.title).size(size).color(theme.palette.accent).width(Length::Fill));
+	if let Some(report) = &inspector.report {
+	    header = header.push(Text::new(report).size(size));
+	}
+	let header = header
+	    .push(Button::new(&mut inspector.refresh, Text::new("Refresh").size(size), || Message::InspectorRefreshed)
+		  .style(theme.button())
+		  .padding(padding))
+	    .push(Button::new(&mut inspector.copy, Text::new("Copy").size(size), || Message::InspectorCopied)
+		  .style(theme.button())
+		  .padding(padding))
+	    .push(Button::new(&mut inspector.close, Text::new("Close").size(size), || Message::InspectorClosed)
+		  .style(theme.button())
+		  .padding(padding));
+
+	let line = |label: &str, value: &str| {
+	    Row::new()
+		.spacing(layout.spacing())
+		.push(Text::new(label).size(size).color(theme.palette.muted).width(Length::FillPortion(1)))
+		.push(Text::new(value).size(size).width(Length::FillPortion(2)))
+	};
+	let fields = details.fields.iter()
+	    .chain(details.properties.iter())
+	    .fold(Column::new().spacing(layout.spacing() / 2), |column, (label, value)| {
+		column.push(line(label, value))
+	    });
+
+	Column::new()
+	    .spacing(layout.spacing())
+	    .height(Length::FillPortion(1))
+	    .push(header)
+	    .push(Scrollable::new(&mut inspector.scroll)
+		  .width(Length::Fill)
+		  .height(Length::Fill)
+		  .push(fields))
+	    .into()
+    }
+
+    fn create_filter<'a>(filter:       &'a mut FilterState,
+			 streams:      &StreamsConfig,
+			 hidden_count: usize,
+			 theme:        &Theme,
+			 layout:       Layout) -> Element<'a, Message> {
+	let size    = layout.text_size();
+	let padding = layout.toggle_padding();
+	let toggle  = |state:     &'a mut button::State,
+		       is_on:     bool,
+		       label:     &str,
+		       on_toggle: fn(bool) -> Message| {
+	    Toggle::new(state, is_on, Text::new(label).size(size), on_toggle)
+		.style(theme.toggle())
+		.padding(padding)
+	};
+	let reveal_label = format!("Show hidden ({})", hidden_count);
+
+	Row::new()
+	    .spacing(layout.spacing())
+	    .align_items(Align::Center)
+	    .push(TextInput::new(&mut filter.search_input,
+				 "Search",
+				 &filter.search,
+				 Message::SearchChanged)
+		  .size(size)
+		  .padding(padding))
+	    .push(toggle(&mut filter.events_toggle,
+			 streams.hide_events,
+			 "Hide event sounds",
+			 Message::HideEventsToggled))
+	    .push(toggle(&mut filter.own_toggle,
+			 streams.hide_own,
+			 "Hide own streams",
+			 Message::HideOwnToggled))
+	    .push(toggle(&mut filter.group_toggle,
+			 streams.group,
+			 "Group by application",
+			 Message::GroupingToggled))
+	    .push(toggle(&mut filter.reveal_toggle,
+			 filter.show_hidden,
+			 &reveal_label,
+			 Message::ShowHiddenToggled))
+	    .into()
+    }
+
+    /// The bar linking the chosen rows, shown while some are chosen.
+    fn create_link_bar<'a>(state:  &'a mut LinkState,
+			   theme:  &Theme,
+			   layout: Layout) -> Element<'a, Message> {
+	let size      = layout.text_size();
+	let padding   = layout.toggle_padding();
+	let can_link  = state.selected.len() > 1;
+	let text      = format!("{} chosen", state.selected.len());
+
+	Row::new()
+	    .spacing(layout.spacing())
+	    .align_items(Align::Center)
+	    .push(Text::new(text).size(size).width(Length::Fill))
+	    .push(Button::new(&mut state.absolute,
+			      Text::new("Same volume").size(size),
+			      || Message::LinkCreated(LinkMode::Absolute))
+		  .disabled(!can_link)
+		  .style(theme.button())
+		  .padding(padding))
+	    .push(Button::new(&mut state.relative,
+			      Text::new("Same change").size(size),
+			      || Message::LinkCreated(LinkMode::Relative))
+		  .disabled(!can_link)
+		  .style(theme.button())
+		  .padding(padding))
+	    .push(Button::new(&mut state.cancel,
+			      Text::new("Cancel").size(size),
+			      || Message::LinkCancelled)
+		  .style(theme.button())
+		  .padding(padding))
+	    .into()
+    }
+
+    fn create_devices_page<'a>(devices: &'a mut DeviceState,
+			       modules: &[Tracked],
+			       theme:   &Theme,
+			       layout:  Layout) -> Element<'a, Message> {
+	let size    = layout.text_size();
+	let padding = layout.toggle_padding();
+	let option  = |state:        &'a mut button::State,
+		       label:        String,
+		       is_active:    bool,
+		       is_available: bool,
+		       on_select:    Message| {
+	    let label = if is_available { label } else { format!("{} (unavailable)", label) };
+	    Toggle::new(state,
+			is_active,
+			Text::new(label).size(size).width(Length::Fill),
+			move |_| on_select.clone())
+		.disabled(is_active || !is_available)
+		.style(theme.toggle())
 		.width(Length::Fill)
-		.vertical_alignment(VerticalAlignment::Center)
-		.horizontal_alignment(HorizontalAlignment::Center)
-	} else {
-	    Text::new("Unmute")
+		.padding(padding)
+	};
+	let heading = |title: &str| Text::new(title).size(size).color(theme.palette.accent);
+
+	let column = Column::new().spacing(layout.spacing()).push(heading("Cards"));
+	let column = devices.cards.iter_mut().fold(column, |column, entry| {
+	    let card    = &entry.card;
+	    let options = entry.profiles.iter_mut()
+		.zip(card.profiles.iter())
+		.fold(Column::new().spacing(layout.spacing() / 2), |options, (state, profile)| {
+		    let is_active = card.active_profile.as_ref() == Some(&profile.name);
+		    options.push(option(state,
+					profile.description.clone(),
+					is_active,
+					profile.available,
+					Message::CardProfileSelected(card.index, profile.name.clone())))
+		});
+
+	    column
+		.push(Text::new(&card.description).size(size))
+		.push(options)
+	});
+
+	let mut column = column;
+	let groups: Vec<(&str, &'a mut Vec<PortEntry>, fn(u32, String) -> Message)> = vec![
+	    ("Output ports", &mut devices.sinks,   Message::SinkPortSelected),
+	    ("Input ports",  &mut devices.sources, Message::SourcePortSelected),
+	];
+	for (title, entries, on_select) in groups {
+	    column = column.push(heading(title));
+	    // Devices without ports, such as virtual ones, have nothing to choose.
+	    for entry in entries.into_iter().filter(|entry| !entry.device.ports.is_empty()) {
+		let device  = &entry.device;
+		let options = entry.ports.iter_mut()
+		    .zip(device.ports.iter())
+		    .fold(Column::new().spacing(layout.spacing() / 2), |options, (state, port)| {
+			let is_active = device.active_port.as_ref() == Some(&port.name);
+			options.push(option(state,
+					    port.description.clone(),
+					    is_active,
+					    port.available != Availability::Unavailable,
+					    on_select(device.index, port.name.clone())))
+		    });
+
+		column = column
+		    .push(Text::new(&device.description).size(size))
+		    .push(options);
+	    }
+	}
+
+	let column = column
+	    .push(heading("Virtual devices"))
+	    .push(Self::create_modules_form(&mut devices.modules, modules, theme, layout));
+
+	Scrollable::new(&mut devices.scroll)
+	    .width(Length::Fill)
+	    .height(Length::Fill)
+	    .push(column)
+	    .into()
+    }
+
+    fn create_modules_form<'a>(form:    &'a mut ModuleState,
+			       modules: &[Tracked],
+			       theme:   &Theme,
+			       layout:  Layout) -> Element<'a, Message> {
+	let size    = layout.text_size();
+	let padding = layout.toggle_padding();
+	let kind    = form.kind;
+
+	let kinds = form.kind_buttons.iter_mut()
+	    .zip(ModuleKind::ALL.iter())
+	    .fold(Row::new().spacing(layout.spacing()), |row, (state, &option)| {
+		row.push(Button::new(state,
+				     Text::new(option.name()).size(size),
+				     move || Message::ModuleKindSelected(option))
+			 .disabled(option == kind)
+			 .style(theme.button())
+			 .padding(padding))
+	    });
+
+	let mut fields = Row::new().spacing(layout.spacing()).align_items(Align::Center);
+	if kind.has_name() {
+	    fields = fields.push(TextInput::new(&mut form.name_input,
+						"Name",
+						&form.name,
+						Message::ModuleNameChanged)
+				 .size(size)
+				 .padding(padding));
+	}
+	if kind.has_source() {
+	    fields = fields.push(TextInput::new(&mut form.source_input,
+						"Source",
+						&form.source,
+						Message::ModuleSourceChanged)
+				 .size(size)
+				 .padding(padding));
+	}
+	let sink_placeholder = if kind == ModuleKind::CombineSink { "Sinks, comma separated" } else { "Sink" };
+	if kind != ModuleKind::NullSink {
+	    fields = fields.push(TextInput::new(&mut form.sink_input,
+						sink_placeholder,
+						&form.sink,
+						Message::ModuleSinkChanged)
+				 .size(size)
+				 .padding(padding));
+	}
+
+	let create = Row::new()
+	    .spacing(layout.spacing())
+	    .align_items(Align::Center)
+	    .push(Toggle::new(&mut form.recreate_toggle,
+			      form.recreate,
+			      Text::new("Recreate at start").size(size),
+			      Message::ModuleRecreateToggled)
+		  .style(theme.toggle())
+		  .padding(padding))
+	    .push(Button::new(&mut form.create,
+			      Text::new("Create").size(size),
+			      || Message::ModuleCreated)
+		  .style(theme.button())
+		  .padding(padding));
+
+	let tracked = form.remove.iter_mut()
+	    .zip(modules.iter())
+	    .enumerate()
+	    .fold(Column::new().spacing(layout.spacing() / 2), |column, (position, (state, module))| {
+		let recreate = if module.recreate { " (recreated at start)" } else { "" };
+		column.push(Row::new()
+			    .spacing(layout.spacing())
+			    .align_items(Align::Center)
+			    .push(Text::new(format!("{}{}", module.setup.describe(), recreate))
+				  .size(size)
+				  .width(Length::Fill))
+			    .push(Button::new(state,
+					      Text::new("Remove").size(size),
+					      move || Message::ModuleRemoved(position))
+				  .style(theme.button())
+				  .padding(padding)))
+	    });
+
+	let column = Column::new()
+	    .spacing(layout.spacing())
+	    .push(kinds)
+	    .push(fields)
+	    .push(create)
+	    .push(tracked);
+
+	match &form.report {
+	    Some(report) => column.push(Text::new(report).size(size)).into(),
+	    None         => column.into(),
+	}
+    }
+
+    fn create_scenes_page<'a>(scenes: &'a mut SceneState,
+			      theme:  &Theme,
+			      layout: Layout) -> Element<'a, Message> {
+	let size    = layout.text_size();
+	let padding = layout.toggle_padding();
+
+	let name = TextInput::new(&mut scenes.name_input,
+				  "Scene name",
+				  &scenes.name,
+				  Message::SceneNameChanged)
+	    .size(size)
+	    .padding(padding)
+	    .on_submit(Message::SceneSaved);
+	let save = Button::new(&mut scenes.save,
+			       Text::new("Save current").size(size),
+			       || Message::SceneSaved)
+	    .disabled(scenes.name.trim().is_empty())
+	    .style(theme.button())
+	    .padding(padding);
+
+	let entries = scenes.entries.iter_mut()
+	    .fold(Column::new().spacing(layout.spacing()), |column, entry| {
+		let apply_name = entry.name.clone();
+		let diff_name  = entry.name.clone();
+
+		column.push(Row::new()
+			    .align_items(Align::Center)
+			    .spacing(layout.spacing())
+			    .push(Text::new(&entry.name).size(size).width(Length::Fill))
+			    .push(Button::new(&mut entry.diff,
+					      Text::new("Compare").size(size),
+					      move || Message::SceneDiffed(diff_name.clone()))
+				  .style(theme.button())
+				  .padding(padding))
+			    .push(Button::new(&mut entry.apply,
+					      Text::new("Apply").size(size),
+					      move || Message::SceneApplied(apply_name.clone()))
+				  .style(theme.button())
+				  .padding(padding)))
+	    });
+
+	let report = scenes.report.iter()
+	    .fold(Column::new().spacing(layout.spacing() / 2), |column, line| {
+		column.push(Text::new(line).size(size))
+	    });
+
+	Scrollable::new(&mut scenes.scroll)
+	    .width(Length::Fill)
+	    .height(Length::Fill)
+	    .spacing(layout.spacing())
+	    .push(Row::new()
+		  .align_items(Align::Center)
+		  .spacing(layout.spacing())
+		  .push(name)
+		  .push(save))
+	    .push(entries)
+	    .push(report)
+	    .into()
+    }
+
+    fn create_history_page<'a>(state:   &'a mut HistoryState,
+			       history: &History,
+			       theme:   &Theme,
+			       layout:  Layout) -> Element<'a, Message> {
+	let size    = layout.text_size();
+	let padding = layout.toggle_padding();
+
+	let buttons = Row::new()
+	    .spacing(layout.spacing())
+	    .push(Button::new(&mut state.undo, Text::new("Undo").size(size), || Message::Undo)
+		  .disabled(!history.can_undo())
+		  .style(theme.button())
+		  .padding(padding))
+	    .push(Button::new(&mut state.redo, Text::new("Redo").size(size), || Message::Redo)
+		  .disabled(!history.can_redo())
+		  .style(theme.button())
+		  .padding(padding));
+
+	history.entries()
+	    .fold(Scrollable::new(&mut state.scroll)
+		  .width(Length::Fill)
+		  .height(Length::Fill)
+		  .spacing(layout.spacing() / 2)
+		  .push(buttons),
+		  |scrollable, entry| scrollable.push(Text::new(entry.describe()).size(size)))
+	    .into()
+    }
+
+    fn create_sleep_page<'a>(state:  &'a mut SleepState,
+			     timer:  Option<&SleepTimer>,
+			     theme:  &Theme,
+			     layout: Layout) -> Element<'a, Message> {
+	let size              = layout.text_size();
+	let padding           = layout.toggle_padding();
+	let applications_only = state.applications_only;
+
+	let controls = match timer {
+	    Some(timer) => Row::new()
+		.align_items(Align::Center)
+		.spacing(layout.spacing())
+		.push(Text::new(format!("Silencing in {}", timer.countdown()))
+		      .size(size)
+		      .width(Length::Fill))
+		.push(Button::new(&mut state.extend,
+				  Text::new("Extend").size(size),
+				  || Message::SleepExtended)
+		      .style(theme.button())
+		      .padding(padding))
+		.push(Button::new(&mut state.cancel,
+				  Text::new("Cancel").size(size),
+				  || Message::SleepCancelled)
+		      .style(theme.button())
+		      .padding(padding)),
+	    None        => {
+		let has_target = !applications_only
+		    || state.applications.iter().any(|application| application.is_selected);
+
+		Row::new()
+		    .align_items(Align::Center)
+		    .spacing(layout.spacing())
+		    .push(TextInput::new(&mut state.delay_input,
+					 "Minutes or HH:MM",
+					 &state.delay,
+					 Message::SleepDelayChanged)
+			  .size(size)
+			  .padding(padding)
+			  .on_submit(Message::SleepStarted))
+		    .push(Button::new(&mut state.start,
+				      Text::new("Start").size(size),
+				      || Message::SleepStarted)
+			  .disabled(state.delay.trim().is_empty() || !has_target)
+			  .style(theme.button())
+			  .padding(padding))
+	    }
+	};
+
+	let toggle = |state:     &'a mut button::State,
+		      is_on:     bool,
+		      label:     &str,
+		      on_toggle: Box<dyn Fn(bool) -> Message>| {
+	    Toggle::new(state, is_on, Text::new(label).size(size).width(Length::Fill), on_toggle)
+		.style(theme.toggle())
 		.width(Length::Fill)
-		.vertical_alignment(VerticalAlignment::Center)
-		.horizontal_alignment(HorizontalAlignment::Center)
+		.padding(padding)
+	};
+
+	let pauses = toggle(&mut state.pause_toggle,
+			    state.pauses,
+			    "Pause the media players",
+			    Box::new(Message::SleepPauseToggled));
+
+	let targets = state.applications.iter_mut()
+	    .fold(Column::new()
+		  .spacing(layout.spacing() / 2)
+		  .push(toggle(&mut state.sink,
+			       !applications_only,
+			       SINK_NAME,
+			       Box::new(Message::SleepSinkToggled))),
+		  |column, application| {
+		let name = application.name.clone();
+		column.push(toggle(&mut application.toggle,
+				   applications_only && application.is_selected,
+				   &application.name,
+				   Box::new(move |is_on| Message::SleepApplicationToggled(name.clone(), is_on))))
+	    });
+
+	let scrollable = Scrollable::new(&mut state.scroll)
+	    .width(Length::Fill)
+	    .height(Length::Fill)
+	    .spacing(layout.spacing())
+	    .push(controls)
+	    .push(pauses)
+	    .push(targets);
+
+	match &state.report {
+	    Some(report) => scrollable.push(Text::new(report).size(size)).into(),
+	    None         => scrollable.into(),
 	}
     }
+
+    fn create_limit_notice<'a>(theme:  &Theme,
+			       layout: Layout,
+			       notice: Option<&(String, Instant)>) -> Element<'a, Message> {
+	let text = match notice {
+	    Some((notice, at)) if at.elapsed() < NOTICE_DURATION => notice.as_str(),
+	    _                                                    => "",
+	};
+
+	Text::new(text)
+	    .size(layout.text_size())
+	    .color(theme.palette.muted)
+	    .vertical_alignment(VerticalAlignment::Center)
+	    .into()
+    }
+
+    fn create_sleep_countdown<'a>(theme:  &Theme,
+				  layout: Layout,
+				  timer:  Option<&SleepTimer>) -> Element<'a, Message> {
+	let text = match timer {
+	    Some(timer) => format!("{} {}", SLEEP_ICON, timer.countdown()),
+	    None        => String::new(),
+	};
+
+	Text::new(text)
+	    .size(layout.text_size())
+	    .color(theme.palette.accent)
+	    .vertical_alignment(VerticalAlignment::Center)
+	    .into()
+    }
+
+    fn create_mic_indicator<'a>(theme:   &Theme,
+				layout:  Layout,
+				is_mute: bool,
+				mode:    PttMode) -> Element<'a, Message> {
+	let state = if is_mute { MUTED_LABEL } else { MIC_LIVE_LABEL };
+	let mode  = match mode {
+	    PttMode::Off        => "",
+	    PttMode::PushToTalk => " (PTT)",
+	    PttMode::PushToMute => " (PTM)",
+	};
+	let color = if is_mute { theme.palette.muted } else { theme.palette.accent };
+
+	Text::new(format!("{} {}{}", MIC_ICON, state, mode))
+	    .size(layout.text_size())
+	    .color(color)
+	    .vertical_alignment(VerticalAlignment::Center)
+	    .into()
+    }
+
+    fn create_mute_toggle<'a, F>(state: &'a mut button::State,
+				 theme: &Theme,
+				 layout: Layout,
+				 is_mute: bool,
+				 is_disabled: bool,
+				 on_toggle: F) -> Element<'a, Message>
+    where
+	F: 'static + Fn(bool) -> Message,
+    {
+	let (label, icon) = if is_mute {
+	    (MUTED_LABEL, MUTED_ICON)
+	} else {
+	    (UNMUTED_LABEL, UNMUTED_ICON)
+	};
+	let size = layout.text_size();
+
+	let toggle = match layout {
+	    Layout::Horizontal => Toggle::new(state,
+					      is_mute,
+					      Text::new(label)
+					      .size(size)
+					      .width(Length::Fill)
+					      .vertical_alignment(VerticalAlignment::Center)
+					      .horizontal_alignment(HorizontalAlignment::Center),
+					      on_toggle)
+		.icon(Text::new(icon).size(size).vertical_alignment(VerticalAlignment::Center)),
+	    Layout::Compact | Layout::Vertical => Toggle::new(state,
+							      is_mute,
+							      Text::new(icon)
+							      .size(size)
+							      .width(Length::Fill)
+							      .horizontal_alignment(HorizontalAlignment::Center),
+							      on_toggle),
+	};
+
+	toggle
+	    .disabled(is_disabled)
+	    .style(theme.toggle())
+	    .width(Length::from(layout.toggle_width()))
+	    .padding(layout.toggle_padding())
+	    .into()
+    }
+
+    /// The previous, play/pause and next buttons of a media player.
+    fn create_transport<'a>(previous:   &'a mut button::State,
+			    play_pause: &'a mut button::State,
+			    next:       &'a mut button::State,
+			    theme:      &Theme,
+			    layout:     Layout,
+			    player:     &Player) -> Element<'a, Message> {
+	let size      = layout.text_size();
+	let play_icon = match player.status {
+	    PlaybackStatus::Playing                          => PAUSE_ICON,
+	    PlaybackStatus::Paused | PlaybackStatus::Stopped => PLAY_ICON,
+	};
+	let button    = |state:      &'a mut button::State,
+			 icon:       &str,
+			 action:     mpris::Action,
+			 is_enabled: bool| {
+	    let bus_name = player.bus_name.clone();
+	    Button::new(state,
+			Text::new(icon).size(size),
+			move || Message::PlayerAction(bus_name.clone(), action))
+		.disabled(!is_enabled)
+		.style(theme.button())
+		.padding(layout.toggle_padding())
+	};
+
+	Row::new()
+	    .align_items(Align::Center)
+	    .spacing(layout.spacing() / 2)
+	    .push(button(previous, PREVIOUS_ICON, mpris::Action::Previous, player.can_go_previous))
+	    .push(button(play_pause, play_icon, mpris::Action::PlayPause, true))
+	    .push(button(next, NEXT_ICON, mpris::Action::Next, player.can_go_next))
+	    .into()
+    }
+
+    fn create_fade_button<'a>(state: &'a mut button::State,
+			      theme: &Theme,
+			      layout: Layout,
+			      is_fading: bool,
+			      is_disabled: bool,
+			      target: Target) -> Element<'a, Message> {
+	let (label, icon) = if is_fading {
+	    (STOP_FADE_LABEL, STOP_FADE_ICON)
+	} else {
+	    (FADE_LABEL, FADE_ICON)
+	};
+	let content = match layout {
+	    Layout::Horizontal                 => label,
+	    Layout::Compact | Layout::Vertical => icon,
+	};
+
+	Button::new(state,
+		    Text::new(content).size(layout.text_size()),
+		    move || Message::FadeOutPressed(target))
+	    .disabled(is_disabled && !is_fading)
+	    .style(theme.button())
+	    .padding(layout.toggle_padding())
+	    .into()
+    }
+
+    /// Chooses the row to be linked, or takes it out of its link.
+    fn create_link_toggle<'a>(state: &'a mut button::State,
+			      theme: &Theme,
+			      layout: Layout,
+			      is_selected: bool,
+			      link: Option<usize>,
+			      target: Target) -> Element<'a, Message> {
+	let label   = match layout {
+	    Layout::Horizontal                 => LINK_LABEL,
+	    Layout::Compact | Layout::Vertical => LINK_ICON,
+	};
+	// The number tells the links apart.
+	let content = match link {
+	    Some(number) => format!("{} {}", label, number),
+	    None         => String::from(label),
+	};
+
+	Toggle::new(state,
+		    is_selected || link.is_some(),
+		    Text::new(content).size(layout.text_size()),
+		    move |is_on| Message::LinkToggled(target, is_on))
+	    .style(theme.toggle())
+	    .padding(layout.toggle_padding())
+	    .into()
+    }
+
+    fn create_expand_button<'a>(state: &'a mut button::State,
+				theme: &Theme,
+				layout: Layout,
+				name: String,
+				is_expanded: bool) -> Element<'a, Message> {
+	let (label, icon) = if is_expanded {
+	    (COLLAPSE_LABEL, COLLAPSE_ICON)
+	} else {
+	    (EXPAND_LABEL, EXPAND_ICON)
+	};
+	let content = match layout {
+	    Layout::Horizontal                 => label,
+	    Layout::Compact | Layout::Vertical => icon,
+	};
+
+	Button::new(state,
+		    Text::new(content).size(layout.text_size()),
+		    move || Message::GroupExpanded(name.clone(), !is_expanded))
+	    .style(theme.button())
+	    .padding(layout.toggle_padding())
+	    .into()
+    }
+
+    /// The button hiding the streams of an application, or showing them
+    /// again when the hidden ones are revealed.
+    fn create_hide_button<'a>(state: &'a mut button::State,
+			      theme: &Theme,
+			      layout: Layout,
+			      name: String,
+			      is_hidden: bool) -> Element<'a, Message> {
+	let (label, icon) = if is_hidden {
+	    (UNHIDE_LABEL, UNHIDE_ICON)
+	} else {
+	    (HIDE_LABEL, HIDE_ICON)
+	};
+	let content = match layout {
+	    Layout::Horizontal                 => label,
+	    Layout::Compact | Layout::Vertical => icon,
+	};
+
+	Button::new(state,
+		    Text::new(content).size(layout.text_size()),
+		    move || Message::StreamHidden(name.clone(), !is_hidden))
+	    .style(theme.button())
+	    .padding(layout.toggle_padding())
+	    .into()
+    }
+}
+
+/// The top of a slider, given the cap of its row.
+fn slider_max(cap: Option<u32>) -> u32 {
+    cap.map_or(MAX_VOLUME, |cap| cap.min(MAX_VOLUME))
+}
+
+/// Replaces the volume or the mute, returning the value it had.
+fn replace_change(volume: &mut u32, mute: &mut bool, change: Change) -> Change {
+    match change {
+	Change::Volume(new) => Change::Volume(std::mem::replace(volume, new)),
+	Change::Mute(new)   => Change::Mute(std::mem::replace(mute, new)),
+    }
 }
@@ -1,7 +1,9 @@
 use std::cell::RefCell;
 use std::ops::Deref;
 use std::rc::Rc;
+use std::time::Instant;
 
+use pulse::context::subscribe::{Facility, InterestMaskSet, Operation as SubscriptionOperation};
 use pulse::context::{
     flags as context_flags, introspect::Introspector, Context, State as ContextState,
 };
@@ -9,17 +11,25 @@ use pulse::mainloop::standard::{IterateResult, Mainloop};
 use pulse::operation::{Operation, State as OpState};
 use pulse::proplist::{properties, Proplist};
 
+use super::pending::{Pending, Rejected};
+use super::stats::{Stats, ROUND_TRIP};
+
+/// The application name the mixer connects to the server with.
+pub const CLIENT_NAME: &str = "VolumeController";
+
 pub struct PulseHandler {
     pub mainloop: Rc<RefCell<Mainloop>>,
     pub context: Rc<RefCell<Context>>,
     pub introspect: Introspector,
+    pub pending: Pending,
+    pub stats: Stats,
 }
 
 impl PulseHandler {
     pub fn new() -> Option<Self> {
         let mut proplist = Proplist::new().expect("Unable to create proplist");
         proplist
-            .set_str(properties::APPLICATION_NAME, "VolumeController")
+            .set_str(properties::APPLICATION_NAME, CLIENT_NAME)
             .unwrap();
 
         let mainloop = Rc::new(RefCell::new(
@@ -43,7 +53,7 @@ impl PulseHandler {
         loop {
             match mainloop.borrow_mut().iterate(false) {
                 IterateResult::Quit(_) | IterateResult::Err(_) => {
-                    eprintln!("Iterate state was not success, quitting...");
+                    log::error!("Iterate state was not success, quitting...");
                     return None;
                 }
                 IterateResult::Success(_) => {}
@@ -53,7 +63,7 @@ impl PulseHandler {
                     break;
                 }
                 ContextState::Failed | ContextState::Terminated => {
-                    eprintln!("Context state failed/terminated, quitting...");
+                    log::error!("Context state failed/terminated, quitting...");
                     return None;
                 }
                 _ => {}
@@ -65,31 +75,92 @@ impl PulseHandler {
             mainloop,
             context,
             introspect,
+            pending: Pending::default(),
+            stats: Stats::default(),
         })
     }
 
+    /// Blocks until `operation` completed, recording how long it took.
     pub fn wait_for_operation<T: ?Sized>(&mut self, operation: Operation<T>) {
+        let start = Instant::now();
+
         loop {
             match self.mainloop.borrow_mut().iterate(false) {
-                IterateResult::Err(e) => eprintln!("{}", e),
+                IterateResult::Err(e) => log::error!("{}", e),
                 IterateResult::Success(_) => {}
                 IterateResult::Quit(_) => {
-                    eprintln!("Iterate state quit without an error");
+                    log::error!("Iterate state quit without an error");
                     return;
                 }
             }
             match operation.get_state() {
                 OpState::Done => {
+                    self.stats.record(ROUND_TRIP, start.elapsed());
                     break;
                 }
                 OpState::Running => {}
                 OpState::Cancelled => {
-                    eprintln!("Operation cancelled without an error");
+                    log::error!("Operation cancelled without an error");
                     return;
                 }
             }
         }
     }
+
+    /// Calls `callback` on every server event matching `mask`, from
+    /// [`poll`](#method.poll) on.
+    pub fn subscribe<F>(&mut self, mask: InterestMaskSet, callback: F)
+    where
+        F: FnMut(Option<Facility>, Option<SubscriptionOperation>, u32) + 'static,
+    {
+        let op = {
+            let mut context = self.context.borrow_mut();
+            context.set_subscribe_callback(Some(Box::new(callback)));
+            context.subscribe(mask, |_| {})
+        };
+        self.wait_for_operation(op);
+    }
+
+    pub fn poll(&mut self) -> Vec<Rejected> {
+        loop {
+            match self.mainloop.borrow_mut().iterate(false) {
+                IterateResult::Success(0) => break,
+                IterateResult::Success(_) => {}
+                IterateResult::Err(e) => {
+                    log::error!("{}", e);
+                    break;
+                }
+                IterateResult::Quit(_) => {
+                    log::error!("Iterate state quit without an error");
+                    break;
+                }
+            }
+        }
+        self.pending.collect(&mut self.stats)
+    }
+
+    /// Polls until every pending operation completed, returning the
+    /// rejected ones.
+    pub fn settle(&mut self) -> Vec<Rejected> {
+        let mut rejected = Vec::new();
+
+        while !self.pending.is_empty() {
+            match self.mainloop.borrow_mut().iterate(true) {
+                IterateResult::Success(_) => {}
+                IterateResult::Err(e) => {
+                    log::error!("{}", e);
+                    break;
+                }
+                IterateResult::Quit(_) => {
+                    log::error!("Iterate state quit without an error");
+                    break;
+                }
+            }
+            rejected.extend(self.pending.collect(&mut self.stats));
+        }
+
+        rejected
+    }
 }
 
 impl Drop for PulseHandler {
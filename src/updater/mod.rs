@@ -1,31 +1,32 @@
 mod handler;
+mod pending;
+mod stats;
 mod types;
-pub use handler::PulseHandler;
-pub use types::{MainData, SinkInputData};
+pub use handler::{PulseHandler, CLIENT_NAME};
+pub use pending::{Change, Rejected, Target};
+pub use stats::{Span, Stats, Timing, ROUND_TRIP};
+pub use types::{
+    Availability, CardData, Details, DeviceData, MainData, ModuleData, PortData, ProfileData,
+    ServerData, SinkInputData, SourceOutputData,
+};
 
 #[cfg(feature = "another_updater")]
 use types::Counter;
 
-#[cfg(feature = "time")]
-use std::time::SystemTime;
-
-use std::cell::RefCell;
+use std::cell::{Cell, RefCell};
 use std::rc::Rc;
 
 use pulse::callbacks::ListResult;
+use pulse::def::INVALID_INDEX;
 use pulse::volume::{ChannelVolumes, Volume};
 
-use crate::button;
-use iced::slider;
-
 #[cfg(not(feature = "another_updater"))]
-pub fn update_sink_inputs(
+pub fn update_sink_inputs<T: Default>(
     handler: &mut PulseHandler,
     sink_inputs: Rc<RefCell<Vec<SinkInputData>>>,
-    sink_input_uis: &mut Vec<(slider::State, button::State)>,
+    sink_input_uis: &mut Vec<T>,
 ) {
-    #[cfg(feature = "time")]
-    let start = SystemTime::now();
+    let span = Span::enter("Streams update");
 
     let new_sink_inputs: Rc<RefCell<Vec<SinkInputData>>> = Rc::new(RefCell::new(Vec::new()));
     let new_sink_inputs_ref = new_sink_inputs.clone();
@@ -49,8 +50,12 @@ pub fn update_sink_inputs(
             .find(|new| new.id == sink_inputs.get(i).unwrap().id);
 
         if let Some(new) = find_result {
+            if handler.pending.is_pending(Target::SinkInput(new.id)) {
+                continue;
+            }
             sink_inputs.get_mut(i).unwrap().volume = new.volume;
             sink_inputs.get_mut(i).unwrap().mute = new.mute;
+            sink_inputs.get_mut(i).unwrap().title = new.title.clone();
         } else {
             sink_inputs.remove(i);
             sink_input_uis.remove(i);
@@ -64,30 +69,28 @@ pub fn update_sink_inputs(
             .is_none()
         {
             sink_inputs.push(new_sink_input.clone());
-            sink_input_uis.push((slider::State::new(), button::State::new()));
+            sink_input_uis.push(T::default());
         }
     }
 
-    #[cfg(feature = "time")]
-    println!(
-        "Info update for {} s.",
-        SystemTime::now()
-            .duration_since(start)
-            .unwrap()
-            .as_secs_f64()
-    );
+    handler.stats.record(span.label(), span.elapsed());
 }
 
 #[cfg(feature = "another_updater")]
-pub fn update_sink_inputs(
+pub fn update_sink_inputs<T: Default>(
     handler: &mut PulseHandler,
     sink_inputs: Rc<RefCell<Vec<SinkInputData>>>,
-    sink_input_uis: &mut Vec<(slider::State, button::State)>,
+    sink_input_uis: &mut Vec<T>,
 ) {
-    #[cfg(feature = "time")]
-    let start = SystemTime::now();
+    let span = Span::enter("Streams update");
 
     let sink_inputs_ref = sink_inputs.clone();
+    let pending_ids: Vec<u32> = sink_inputs
+        .borrow()
+        .iter()
+        .map(|si| si.id)
+        .filter(|&id| handler.pending.is_pending(Target::SinkInput(id)))
+        .collect();
     let new_ids = Rc::new(RefCell::new(Vec::new()));
     let new_ids_ref = new_ids.clone();
     let appended = Rc::new(RefCell::new(Counter::default()));
@@ -104,6 +107,9 @@ pub fn update_sink_inputs(
                 new_ids_ref.borrow_mut().push(new_si.id);
 
                 if let Some(index) = result {
+                    if pending_ids.contains(&new_si.id) {
+                        return;
+                    }
                     sis[index].volume = new_si.volume;
                     sis[index].mute = new_si.mute;
                 } else {
@@ -116,7 +122,7 @@ pub fn update_sink_inputs(
     handler.wait_for_operation(op);
 
     for _ in 0..appended.borrow().value {
-        sink_input_uis.push((slider::State::new(), button::State::new()))
+        sink_input_uis.push(T::default())
     }
 
     let mut sink_inputs = sink_inputs.borrow_mut();
@@ -128,61 +134,56 @@ pub fn update_sink_inputs(
         }
     }
 
-    #[cfg(feature = "time")]
-    println!(
-        "Data update for {} s.",
-        SystemTime::now()
-            .duration_since(start)
-            .unwrap()
-            .as_secs_f64()
-    );
+    handler.stats.record(span.label(), span.elapsed());
 }
 
-pub fn update_sink_input_volume_by_id(handler: &mut PulseHandler, id: u32, volume: u32) {
-    #[cfg(feature = "time")]
-    let start = SystemTime::now();
-
-    let op = handler
-        .introspect
-        .set_sink_input_volume(id, &convert_volume(volume), None);
-    handler.wait_for_operation(op);
-
-    #[cfg(feature = "time")]
-    println!(
-        "Change sink input volume for {} s.",
-        SystemTime::now()
-            .duration_since(start)
-            .unwrap()
-            .as_secs_f64()
+pub fn update_sink_input_volume_by_id(
+    handler: &mut PulseHandler,
+    id: u32,
+    volume: u32,
+    previous: u32,
+) {
+    let span = Span::enter("Stream volume change");
+
+    let introspect = &mut handler.introspect;
+    handler.pending.track(
+        Target::SinkInput(id),
+        Change::Volume(previous),
+        span.label(),
+        |callback| introspect.set_sink_input_volume(id, &convert_volume(volume), Some(callback)),
     );
 }
 
-pub fn update_sink_input_mute_by_id(handler: &mut PulseHandler, id: u32, status: bool) {
-    #[cfg(feature = "time")]
-    let start = SystemTime::now();
-
-    let op = handler.introspect.set_sink_input_mute(id, status, None);
-    handler.wait_for_operation(op);
-
-    #[cfg(feature = "time")]
-    println!(
-        "Change sink input volume for {} s.",
-        SystemTime::now()
-            .duration_since(start)
-            .unwrap()
-            .as_secs_f64()
+pub fn update_sink_input_mute_by_id(
+    handler: &mut PulseHandler,
+    id: u32,
+    status: bool,
+    previous: bool,
+) {
+    let span = Span::enter("Stream mute change");
+
+    let introspect = &mut handler.introspect;
+    handler.pending.track(
+        Target::SinkInput(id),
+        Change::Mute(previous),
+        span.label(),
+        |callback| introspect.set_sink_input_mute(id, status, Some(callback)),
     );
 }
 
 pub fn update_fetch_sink(handler: &mut PulseHandler, sink: Rc<RefCell<MainData>>) {
-    #[cfg(feature = "time")]
-    let start = SystemTime::now();
+    if handler.pending.is_pending(Target::Sink) {
+        return;
+    }
+
+    let span = Span::enter("Sink update");
 
     let op = handler
         .introspect
         .get_sink_info_by_index(0, move |x| match x {
             ListResult::Item(x) => {
                 let mut sink = sink.borrow_mut();
+                sink.name = x.name.as_deref().unwrap_or_default().to_owned();
                 sink.volume = x.volume.get()[0].0.clone();
                 sink.mute = x.mute.clone();
             }
@@ -190,61 +191,46 @@ pub fn update_fetch_sink(handler: &mut PulseHandler, sink: Rc<RefCell<MainData>>
         });
     handler.wait_for_operation(op);
 
-    #[cfg(feature = "time")]
-    println!(
-        "Change sink input volume for {} s.",
-        SystemTime::now()
-            .duration_since(start)
-            .unwrap()
-            .as_secs_f64()
-    );
+    handler.stats.record(span.label(), span.elapsed());
 }
 
-pub fn update_sink_volume(handler: &mut PulseHandler, volume: u32) {
-    #[cfg(feature = "time")]
-    let start = SystemTime::now();
-
-    let op = handler
-        .introspect
-        .set_sink_volume_by_index(0, &convert_volume(volume), None);
-    handler.wait_for_operation(op);
+pub fn update_sink_volume(handler: &mut PulseHandler, volume: u32, previous: u32) {
+    let span = Span::enter("Sink volume change");
 
-    #[cfg(feature = "time")]
-    println!(
-        "Change sink volume for {} s.",
-        SystemTime::now()
-            .duration_since(start)
-            .unwrap()
-            .as_secs_f64()
+    let introspect = &mut handler.introspect;
+    handler.pending.track(
+        Target::Sink,
+        Change::Volume(previous),
+        span.label(),
+        |callback| introspect.set_sink_volume_by_index(0, &convert_volume(volume), Some(callback)),
     );
 }
 
-pub fn update_sink_mute(handler: &mut PulseHandler, status: bool) {
-    #[cfg(feature = "time")]
-    let start = SystemTime::now();
+pub fn update_sink_mute(handler: &mut PulseHandler, status: bool, previous: bool) {
+    let span = Span::enter("Sink mute change");
 
-    let op = handler.introspect.set_sink_mute_by_index(0, status, None);
-    handler.wait_for_operation(op);
-
-    #[cfg(feature = "time")]
-    println!(
-        "Change sink mute for {} s.",
-        SystemTime::now()
-            .duration_since(start)
-            .unwrap()
-            .as_secs_f64()
+    let introspect = &mut handler.introspect;
+    handler.pending.track(
+        Target::Sink,
+        Change::Mute(previous),
+        span.label(),
+        |callback| introspect.set_sink_mute_by_index(0, status, Some(callback)),
     );
 }
 
 pub fn update_fetch_source(handler: &mut PulseHandler, source: Rc<RefCell<MainData>>) {
-    #[cfg(feature = "time")]
-    let start = SystemTime::now();
+    if handler.pending.is_pending(Target::Source) {
+        return;
+    }
+
+    let span = Span::enter("Source update");
 
     let op = handler
         .introspect
         .get_source_info_by_index(1, move |x| match x {
             ListResult::Item(x) => {
                 let mut source = source.borrow_mut();
+                source.name = x.name.as_deref().unwrap_or_default().to_owned();
                 source.volume = x.volume.get()[0].0.clone();
                 source.mute = x.mute.clone();
             }
@@ -252,50 +238,332 @@ pub fn update_fetch_source(handler: &mut PulseHandler, source: Rc<RefCell<MainDa
         });
     handler.wait_for_operation(op);
 
-    #[cfg(feature = "time")]
-    println!(
-        "Change source input volume for {} s.",
-        SystemTime::now()
-            .duration_since(start)
-            .unwrap()
-            .as_secs_f64()
+    handler.stats.record(span.label(), span.elapsed());
+}
+
+pub fn update_source_volume(handler: &mut PulseHandler, volume: u32, previous: u32) {
+    let span = Span::enter("Source volume change");
+
+    let introspect = &mut handler.introspect;
+    handler.pending.track(
+        Target::Source,
+        Change::Volume(previous),
+        span.label(),
+        |callback| {
+            introspect.set_source_volume_by_index(1, &convert_volume(volume), Some(callback))
+        },
     );
 }
 
-pub fn update_source_volume(handler: &mut PulseHandler, volume: u32) {
-    #[cfg(feature = "time")]
-    let start = SystemTime::now();
+pub fn update_source_mute(handler: &mut PulseHandler, status: bool, previous: bool) {
+    let span = Span::enter("Source mute change");
+
+    let introspect = &mut handler.introspect;
+    handler.pending.track(
+        Target::Source,
+        Change::Mute(previous),
+        span.label(),
+        |callback| introspect.set_source_mute_by_index(1, status, Some(callback)),
+    );
+}
+
+/// Lists the sink inputs of the clients.
+pub fn fetch_sink_inputs(handler: &mut PulseHandler) -> Vec<SinkInputData> {
+    let _span = Span::enter("Streams fetch");
+    let sink_inputs = Rc::new(RefCell::new(Vec::new()));
+    let sink_inputs_ref = sink_inputs.clone();
+
+    let op = handler
+        .introspect
+        .get_sink_input_info_list(move |x| match x {
+            ListResult::Item(item) if item.client.is_some() => {
+                sink_inputs_ref.borrow_mut().push(item.into())
+            }
+            _ => {}
+        });
+    handler.wait_for_operation(op);
+
+    sink_inputs.replace(Vec::new())
+}
+
+/// Lists the source outputs of the clients, i.e. what is recording.
+pub fn fetch_source_outputs(handler: &mut PulseHandler) -> Vec<SourceOutputData> {
+    let _span = Span::enter("Recording streams fetch");
+    let source_outputs = Rc::new(RefCell::new(Vec::new()));
+    let source_outputs_ref = source_outputs.clone();
 
     let op = handler
         .introspect
-        .set_source_volume_by_index(1, &convert_volume(volume), None);
+        .get_source_output_info_list(move |x| match x {
+            ListResult::Item(item) if item.client.is_some() => {
+                source_outputs_ref.borrow_mut().push(item.into())
+            }
+            _ => {}
+        });
     handler.wait_for_operation(op);
 
-    #[cfg(feature = "time")]
-    println!(
-        "Change source volume for {} s.",
-        SystemTime::now()
-            .duration_since(start)
-            .unwrap()
-            .as_secs_f64()
-    );
+    source_outputs.replace(Vec::new())
+}
+
+pub fn fetch_server_info(handler: &mut PulseHandler) -> ServerData {
+    let _span = Span::enter("Server info fetch");
+    let server = Rc::new(RefCell::new(ServerData::default()));
+    let server_ref = server.clone();
+
+    let op = handler
+        .introspect
+        .get_server_info(move |info| *server_ref.borrow_mut() = info.into());
+    handler.wait_for_operation(op);
+
+    server.replace(ServerData::default())
+}
+
+pub fn fetch_sinks(handler: &mut PulseHandler) -> Vec<DeviceData> {
+    let _span = Span::enter("Sinks fetch");
+    let sinks = Rc::new(RefCell::new(Vec::new()));
+    let sinks_ref = sinks.clone();
+
+    let op = handler.introspect.get_sink_info_list(move |x| {
+        if let ListResult::Item(item) = x {
+            sinks_ref.borrow_mut().push(item.into());
+        }
+    });
+    handler.wait_for_operation(op);
+
+    sinks.replace(Vec::new())
+}
+
+pub fn fetch_sources(handler: &mut PulseHandler) -> Vec<DeviceData> {
+    let _span = Span::enter("Sources fetch");
+    let sources = Rc::new(RefCell::new(Vec::new()));
+    let sources_ref = sources.clone();
+
+    let op = handler.introspect.get_source_info_list(move |x| {
+        if let ListResult::Item(item) = x {
+            sources_ref.borrow_mut().push(item.into());
+        }
+    });
+    handler.wait_for_operation(op);
+
+    sources.replace(Vec::new())
+}
+
+pub fn fetch_cards(handler: &mut PulseHandler) -> Vec<CardData> {
+    let _span = Span::enter("Cards fetch");
+    let cards = Rc::new(RefCell::new(Vec::new()));
+    let cards_ref = cards.clone();
+
+    let op = handler.introspect.get_card_info_list(move |x| {
+        if let ListResult::Item(item) = x {
+            cards_ref.borrow_mut().push(item.into());
+        }
+    });
+    handler.wait_for_operation(op);
+
+    cards.replace(Vec::new())
 }
 
-pub fn update_source_mute(handler: &mut PulseHandler, status: bool) {
-    #[cfg(feature = "time")]
-    let start = SystemTime::now();
+pub fn update_card_profile(handler: &mut PulseHandler, index: u32, profile: &str) {
+    let _span = Span::enter("Card profile change");
+    let op = handler
+        .introspect
+        .set_card_profile_by_index(index, profile, None);
+    handler.wait_for_operation(op);
+}
 
-    let op = handler.introspect.set_source_mute_by_index(1, status, None);
+pub fn update_sink_port(handler: &mut PulseHandler, index: u32, port: &str) {
+    let _span = Span::enter("Sink port change");
+    let op = handler.introspect.set_sink_port_by_index(index, port, None);
     handler.wait_for_operation(op);
+}
 
-    #[cfg(feature = "time")]
-    println!(
-        "Change source mute for {} s.",
-        SystemTime::now()
-            .duration_since(start)
-            .unwrap()
-            .as_secs_f64()
+pub fn update_source_port(handler: &mut PulseHandler, index: u32, port: &str) {
+    let _span = Span::enter("Source port change");
+    let op = handler
+        .introspect
+        .set_source_port_by_index(index, port, None);
+    handler.wait_for_operation(op);
+}
+
+/// Sets the volume of any sink, out of the tracking of the pending changes.
+pub fn update_sink_volume_by_index(handler: &mut PulseHandler, index: u32, volume: u32) -> bool {
+    let _span = Span::enter("Sink volume change by index");
+    let success = Rc::new(Cell::new(false));
+    let success_ref = success.clone();
+
+    let op = handler.introspect.set_sink_volume_by_index(
+        index,
+        &convert_volume(volume),
+        Some(Box::new(move |success| success_ref.set(success))),
     );
+    handler.wait_for_operation(op);
+
+    success.get()
+}
+
+pub fn fetch_modules(handler: &mut PulseHandler) -> Vec<ModuleData> {
+    let _span = Span::enter("Modules fetch");
+    let modules = Rc::new(RefCell::new(Vec::new()));
+    let modules_ref = modules.clone();
+
+    let op = handler.introspect.get_module_info_list(move |x| {
+        if let ListResult::Item(item) = x {
+            modules_ref.borrow_mut().push(item.into());
+        }
+    });
+    handler.wait_for_operation(op);
+
+    modules.replace(Vec::new())
+}
+
+/// Loads a module, returning its index, or `None` when the server refused
+/// it.
+pub fn load_module(handler: &mut PulseHandler, name: &str, argument: &str) -> Option<u32> {
+    let _span = Span::enter("Module load");
+    let index = Rc::new(Cell::new(INVALID_INDEX));
+    let index_ref = index.clone();
+
+    let op = handler
+        .introspect
+        .load_module(name, argument, move |index| index_ref.set(index));
+    handler.wait_for_operation(op);
+
+    Some(index.get()).filter(|&index| index != INVALID_INDEX)
+}
+
+pub fn unload_module(handler: &mut PulseHandler, index: u32) -> bool {
+    let _span = Span::enter("Module unload");
+    let success = Rc::new(Cell::new(false));
+    let success_ref = success.clone();
+
+    let op = handler
+        .introspect
+        .unload_module(index, move |success| success_ref.set(success));
+    handler.wait_for_operation(op);
+
+    success.get()
+}
+
+/// Fetches the details of a stream or device, or `None` when it is gone.
+pub fn fetch_details(handler: &mut PulseHandler, target: Target) -> Option<Details> {
+    let _span = Span::enter("Details fetch");
+    let details = Rc::new(RefCell::new(None));
+    let details_ref = details.clone();
+    let mut store = move |item: Details| *details_ref.borrow_mut() = Some(item);
+
+    match target {
+        Target::SinkInput(id) => {
+            let op = handler.introspect.get_sink_input_info(id, move |x| {
+                if let ListResult::Item(item) = x {
+                    store(item.into());
+                }
+            });
+            handler.wait_for_operation(op);
+        }
+        Target::Sink => {
+            let op = handler.introspect.get_sink_info_by_index(0, move |x| {
+                if let ListResult::Item(item) = x {
+                    store(item.into());
+                }
+            });
+            handler.wait_for_operation(op);
+        }
+        Target::Source => {
+            let op = handler.introspect.get_source_info_by_index(1, move |x| {
+                if let ListResult::Item(item) = x {
+                    store(item.into());
+                }
+            });
+            handler.wait_for_operation(op);
+        }
+    }
+
+    let mut details = details.replace(None)?;
+    // Name the client, which the stream only knows by index.
+    let client = details
+        .fields
+        .iter_mut()
+        .find(|(label, _)| label == "Client")
+        .and_then(|(_, value)| Some((value.parse::<u32>().ok()?, value)));
+    if let Some((index, value)) = client {
+        let name = Rc::new(RefCell::new(None));
+        let name_ref = name.clone();
+        let op = handler.introspect.get_client_info(index, move |x| {
+            if let ListResult::Item(item) = x {
+                *name_ref.borrow_mut() = item.name.as_deref().map(String::from);
+            }
+        });
+        handler.wait_for_operation(op);
+
+        if let Some(name) = name.replace(None) {
+            *value = format!("{} (#{})", name, index);
+        }
+    }
+
+    Some(details)
+}
+
+pub fn fetch_client_count(handler: &mut PulseHandler) -> usize {
+    let _span = Span::enter("Clients count");
+    let count = Rc::new(Cell::new(0));
+    let count_ref = count.clone();
+
+    let op = handler.introspect.get_client_info_list(move |x| {
+        if let ListResult::Item(_) = x {
+            count_ref.set(count_ref.get() + 1);
+        }
+    });
+    handler.wait_for_operation(op);
+
+    count.get()
+}
+
+pub fn move_sink_input_by_id(handler: &mut PulseHandler, id: u32, sink: u32) {
+    let _span = Span::enter("Stream move");
+    let op = handler.introspect.move_sink_input_by_index(id, sink, None);
+    handler.wait_for_operation(op);
+}
+
+pub fn update_default_sink(handler: &mut PulseHandler, name: &str) {
+    let _span = Span::enter("Default sink change");
+    let op = handler.context.borrow_mut().set_default_sink(name, |_| {});
+    handler.wait_for_operation(op);
+}
+
+pub fn update_default_source(handler: &mut PulseHandler, name: &str) {
+    let _span = Span::enter("Default source change");
+    let op = handler
+        .context
+        .borrow_mut()
+        .set_default_source(name, |_| {});
+    handler.wait_for_operation(op);
+}
+
+/// Sets the volume and mute of any sink, out of the tracking of the pending
+/// changes.
+pub fn update_sink_level_by_index(handler: &mut PulseHandler, index: u32, volume: u32, mute: bool) {
+    let _span = Span::enter("Sink level change by index");
+    let introspect = &mut handler.introspect;
+    let volume_op = introspect.set_sink_volume_by_index(index, &convert_volume(volume), None);
+    let mute_op = introspect.set_sink_mute_by_index(index, mute, None);
+    handler.wait_for_operation(volume_op);
+    handler.wait_for_operation(mute_op);
+}
+
+/// Sets the volume and mute of any source, out of the tracking of the
+/// pending changes.
+pub fn update_source_level_by_index(
+    handler: &mut PulseHandler,
+    index: u32,
+    volume: u32,
+    mute: bool,
+) {
+    let _span = Span::enter("Source level change by index");
+    let introspect = &mut handler.introspect;
+    let volume_op = introspect.set_source_volume_by_index(index, &convert_volume(volume), None);
+    let mute_op = introspect.set_source_mute_by_index(index, mute, None);
+    handler.wait_for_operation(volume_op);
+    handler.wait_for_operation(mute_op);
 }
 
 fn convert_volume(volume: u32) -> ChannelVolumes {
@@ -0,0 +1,274 @@
+use std::cell::Cell;
+use std::rc::Rc;
+use std::time::{Duration, Instant};
+
+use pulse::operation::{Operation, State as OpState};
+
+use super::stats::Stats;
+
+/// The stream or device an operation changes.
+#[derive(Clone, Copy, Debug, PartialEq, Eq, Hash)]
+pub enum Target {
+    SinkInput(u32),
+    Sink,
+    Source,
+}
+
+/// A value of a [`Target`], as it was before an operation changed it.
+#[derive(Clone, Copy, Debug, PartialEq)]
+pub enum Change {
+    Volume(u32),
+    Mute(bool),
+}
+
+impl Change {
+    fn same_kind(&self, other: &Change) -> bool {
+        matches!(
+            (self, other),
+            (Change::Volume(_), Change::Volume(_)) | (Change::Mute(_), Change::Mute(_))
+        )
+    }
+}
+
+/// An operation the server did not apply.
+///
+/// `previous` is the value the target should be rolled back to.
+#[derive(Clone, Copy, Debug)]
+pub struct Rejected {
+    pub target: Target,
+    pub previous: Change,
+}
+
+/// An operation sent to the server, as far as its tracking goes.
+trait Sent {
+    fn is_cancelled(&self) -> bool;
+}
+
+impl Sent for Operation<dyn FnMut(bool)> {
+    fn is_cancelled(&self) -> bool {
+        self.get_state() == OpState::Cancelled
+    }
+}
+
+struct Entry {
+    target: Target,
+    previous: Change,
+    /// The label of the timing of the operation.
+    label: &'static str,
+    operation: Box<dyn Sent>,
+    /// The success, and how long the server took to answer.
+    result: Rc<Cell<Option<(bool, Duration)>>>,
+}
+
+/// Operations sent to the server which have not completed yet.
+#[derive(Default)]
+pub struct Pending {
+    entries: Vec<Entry>,
+}
+
+impl Pending {
+    /// Starts tracking the operation created by `start`.
+    ///
+    /// `start` receives the success callback to hand over to the
+    /// introspection call. The time until the server answered is recorded
+    /// under `label` once the operation is collected.
+    pub fn track<F>(&mut self, target: Target, previous: Change, label: &'static str, start: F)
+    where
+        F: FnOnce(Box<dyn FnMut(bool)>) -> Operation<dyn FnMut(bool)>,
+    {
+        let result = Rc::new(Cell::new(None));
+        let result_ref = result.clone();
+        let sent_at = Instant::now();
+
+        let operation = start(Box::new(move |success| {
+            result_ref.set(Some((success, sent_at.elapsed())))
+        }));
+
+        self.entries.push(Entry {
+            target,
+            previous,
+            label,
+            operation: Box::new(operation),
+            result,
+        });
+    }
+
+    /// Returns `true` while any operation on `target` is in flight.
+    pub fn is_pending(&self, target: Target) -> bool {
+        self.entries.iter().any(|entry| entry.target == target)
+    }
+
+    /// Returns `true` once every operation sent completed and was
+    /// collected.
+    pub fn is_empty(&self) -> bool {
+        self.entries.is_empty()
+    }
+
+    /// Forgets completed operations, recording their timings, and returns
+    /// the rejected ones.
+    ///
+    /// A rejection is only reported when no later change of the same kind
+    /// was sent for its target, as that change supersedes the rollback.
+    pub fn collect(&mut self, stats: &mut Stats) -> Vec<Rejected> {
+        let mut rejected = Vec::new();
+        let mut index = 0;
+
+        while index < self.entries.len() {
+            let entry = &self.entries[index];
+            let success = match entry.result.get() {
+                Some((success, elapsed)) => {
+                    stats.record(entry.label, elapsed);
+                    success
+                }
+                None if entry.operation.is_cancelled() => false,
+                None => {
+                    index += 1;
+                    continue;
+                }
+            };
+
+            let entry = self.entries.remove(index);
+            let is_superseded = self.entries[index..].iter().any(|later| {
+                later.target == entry.target && later.previous.same_kind(&entry.previous)
+            });
+
+            if !success && !is_superseded {
+                rejected.push(Rejected {
+                    target: entry.target,
+                    previous: entry.previous,
+                });
+            }
+        }
+
+        rejected
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    const LABEL: &str = "Test change";
+
+    /// The answer of the server to an operation of a test.
+    struct Answer {
+        result: Rc<Cell<Option<(bool, Duration)>>>,
+        is_cancelled: Rc<Cell<bool>>,
+    }
+
+    impl Answer {
+        fn reply(&self, success: bool) {
+            self.result.set(Some((success, Duration::from_millis(5))));
+        }
+
+        fn cancel(&self) {
+            self.is_cancelled.set(true);
+        }
+    }
+
+    struct FakeOperation(Rc<Cell<bool>>);
+
+    impl Sent for FakeOperation {
+        fn is_cancelled(&self) -> bool {
+            self.0.get()
+        }
+    }
+
+    fn send(pending: &mut Pending, target: Target, previous: Change) -> Answer {
+        let answer = Answer {
+            result: Rc::new(Cell::new(None)),
+            is_cancelled: Rc::new(Cell::new(false)),
+        };
+        pending.entries.push(Entry {
+            target,
+            previous,
+            label: LABEL,
+            operation: Box::new(FakeOperation(answer.is_cancelled.clone())),
+            result: answer.result.clone(),
+        });
+        answer
+    }
+
+    fn rejected(pending: &mut Pending) -> Vec<(Target, Change)> {
+        pending
+            .collect(&mut Stats::default())
+            .into_iter()
+            .map(|rejected| (rejected.target, rejected.previous))
+            .collect()
+    }
+
+    #[test]
+    fn keeps_the_operations_the_server_did_not_answer() {
+        let mut pending = Pending::default();
+        send(&mut pending, Target::Sink, Change::Volume(100));
+
+        assert!(rejected(&mut pending).is_empty());
+        assert!(pending.is_pending(Target::Sink));
+        assert!(!pending.is_pending(Target::Source));
+    }
+
+    #[test]
+    fn forgets_the_applied_operations_and_records_their_timings() {
+        let mut pending = Pending::default();
+        let mut stats = Stats::default();
+        send(&mut pending, Target::Sink, Change::Volume(100)).reply(true);
+
+        assert!(pending.collect(&mut stats).is_empty());
+        assert!(pending.is_empty());
+        assert_eq!(stats.get(LABEL).map(|timing| timing.count), Some(1));
+    }
+
+    #[test]
+    fn rolls_back_the_rejected_operations() {
+        let mut pending = Pending::default();
+        send(&mut pending, Target::SinkInput(7), Change::Mute(false)).reply(false);
+
+        assert_eq!(
+            rejected(&mut pending),
+            vec![(Target::SinkInput(7), Change::Mute(false))]
+        );
+        assert!(pending.is_empty());
+    }
+
+    #[test]
+    fn rolls_back_the_cancelled_operations_without_timing_them() {
+        let mut pending = Pending::default();
+        let mut stats = Stats::default();
+        send(&mut pending, Target::Source, Change::Volume(100)).cancel();
+
+        let rejected = pending.collect(&mut stats);
+        assert_eq!(rejected.len(), 1);
+        assert_eq!(rejected[0].previous, Change::Volume(100));
+        assert!(pending.is_empty());
+        assert!(stats.get(LABEL).is_none());
+    }
+
+    #[test]
+    fn skips_the_rollbacks_of_superseded_operations() {
+        let mut pending = Pending::default();
+        send(&mut pending, Target::Sink, Change::Volume(100)).reply(false);
+        let later = send(&mut pending, Target::Sink, Change::Volume(200));
+
+        assert!(rejected(&mut pending).is_empty());
+        assert!(pending.is_pending(Target::Sink));
+
+        later.reply(false);
+        assert_eq!(
+            rejected(&mut pending),
+            vec![(Target::Sink, Change::Volume(200))]
+        );
+    }
+
+    #[test]
+    fn rolls_back_operations_only_superseded_by_another_kind_or_target() {
+        let mut pending = Pending::default();
+        send(&mut pending, Target::Sink, Change::Volume(100)).reply(false);
+        send(&mut pending, Target::Sink, Change::Mute(false));
+        send(&mut pending, Target::Source, Change::Volume(200));
+
+        assert_eq!(
+            rejected(&mut pending),
+            vec![(Target::Sink, Change::Volume(100))]
+        );
+    }
+}
@@ -0,0 +1,89 @@
+//! Timings of the work done with the server, shown on the server page.
+use std::time::{Duration, Instant};
+
+/// The label of the waits for server operations.
+pub const ROUND_TRIP: &str = "Server round trip";
+
+#[derive(Clone, Copy, Debug, Default)]
+pub struct Timing {
+    pub count: u32,
+    pub last: Duration,
+    pub max: Duration,
+    total: Duration,
+}
+
+impl Timing {
+    pub fn average(&self) -> Duration {
+        if self.count == 0 {
+            Duration::from_secs(0)
+        } else {
+            self.total / self.count
+        }
+    }
+}
+
+#[derive(Debug, Default)]
+pub struct Stats {
+    timings: Vec<(&'static str, Timing)>,
+}
+
+impl Stats {
+    pub fn record(&mut self, label: &'static str, elapsed: Duration) {
+        let index = match self.timings.iter().position(|(name, _)| *name == label) {
+            Some(index) => index,
+            None => {
+                self.timings.push((label, Timing::default()));
+                self.timings.len() - 1
+            }
+        };
+
+        let timing = &mut self.timings[index].1;
+        timing.count = timing.count.saturating_add(1);
+        timing.last = elapsed;
+        timing.max = timing.max.max(elapsed);
+        timing.total += elapsed;
+    }
+
+    pub fn get(&self, label: &str) -> Option<Timing> {
+        self.timings
+            .iter()
+            .find(|(name, _)| *name == label)
+            .map(|(_, timing)| *timing)
+    }
+
+    /// The timings, in the order they were first recorded.
+    pub fn timings(&self) -> &[(&'static str, Timing)] {
+        &self.timings
+    }
+}
+
+/// An operation with the server, logged when it starts and when it ends.
+#[derive(Debug)]
+pub struct Span {
+    label: &'static str,
+    start: Instant,
+}
+
+impl Span {
+    pub fn enter(label: &'static str) -> Self {
+        log::trace!("{} started", label);
+        Self {
+            label,
+            start: Instant::now(),
+        }
+    }
+
+    pub fn label(&self) -> &'static str {
+        self.label
+    }
+
+    pub fn elapsed(&self) -> Duration {
+        self.start.elapsed()
+    }
+}
+
+impl Drop for Span {
+    fn drop(&mut self) {
+        log::trace!("{} ended after {:?}", self.label, self.elapsed());
+    }
+}
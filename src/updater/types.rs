@@ -1,6 +1,14 @@
 use pulse::context::introspect;
+use pulse::def::PortAvailable;
+use pulse::proplist::Proplist;
+use pulse::time::MicroSeconds;
 
 const APPLICATION_NAME: &'static str = "application.name";
+const MEDIA_NAME: &'static str = "media.name";
+const MEDIA_ROLE: &'static str = "media.role";
+const PROCESS_ID: &'static str = "application.process.id";
+const PROCESS_BINARY: &'static str = "application.process.binary";
+const DEVICE_DESCRIPTION: &'static str = "device.description";
 
 #[cfg(feature = "another_updater")]
 #[derive(Clone, Debug, Default)]
@@ -19,12 +27,108 @@ impl Counter {
 pub struct SinkInputData {
     pub id: u32,
     pub name: String,
+    /// What the stream plays, such as the title of a tab or a song.
+    pub title: Option<String>,
+    pub role: Option<String>,
+    /// The process playing the stream, when the client tells it.
+    pub pid: Option<u32>,
+    pub binary: Option<String>,
     pub volume: u32,
     pub mute: bool,
 }
 
+#[derive(Clone, Debug, Default)]
+pub struct SourceOutputData {
+    pub id: u32,
+    pub name: String,
+    pub role: Option<String>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct DeviceData {
+    pub index: u32,
+    pub name: String,
+    pub description: String,
+    pub volume: u32,
+    pub mute: bool,
+    pub ports: Vec<PortData>,
+    pub active_port: Option<String>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ModuleData {
+    pub index: u32,
+    pub name: String,
+    pub argument: String,
+}
+
+/// The details of a stream or device, for the inspector.
+#[derive(Clone, Debug, Default)]
+pub struct Details {
+    pub title: String,
+    /// Labelled values, in display order.
+    pub fields: Vec<(String, String)>,
+    /// The property list, sorted by key.
+    pub properties: Vec<(String, String)>,
+}
+
+/// Whether something is plugged into a port, as jack detection tells.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Availability {
+    Unknown,
+    Unavailable,
+    Available,
+}
+
+impl From<PortAvailable> for Availability {
+    fn from(available: PortAvailable) -> Self {
+        match available {
+            PortAvailable::Unknown => Availability::Unknown,
+            PortAvailable::No => Availability::Unavailable,
+            PortAvailable::Yes => Availability::Available,
+        }
+    }
+}
+
+#[derive(Clone, Debug)]
+pub struct PortData {
+    pub name: String,
+    pub description: String,
+    pub available: Availability,
+}
+
+#[derive(Clone, Debug)]
+pub struct ProfileData {
+    pub name: String,
+    pub description: String,
+    pub available: bool,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct CardData {
+    pub index: u32,
+    pub name: String,
+    pub description: String,
+    pub profiles: Vec<ProfileData>,
+    pub active_profile: Option<String>,
+}
+
+#[derive(Clone, Debug, Default)]
+pub struct ServerData {
+    pub default_sink: String,
+    pub default_source: String,
+    pub server_name: String,
+    pub server_version: String,
+    pub host_name: String,
+    pub user_name: String,
+    pub sample_spec: String,
+    pub channel_map: String,
+}
+
 #[derive(Clone, Debug, Default)]
 pub struct MainData {
+    /// The name of the device, such as `alsa_output.pci-0000_00_1f.3.analog-stereo`.
+    pub name: String,
     pub volume: u32,
     pub mute: bool,
 }
@@ -34,16 +138,158 @@ impl<'a> From<&'a introspect::SinkInputInfo<'a>> for SinkInputData {
         Self {
             id: item.index.clone(),
             name: item.proplist.get_str(APPLICATION_NAME).unwrap(),
+            title: item.proplist.get_str(MEDIA_NAME),
+            role: item.proplist.get_str(MEDIA_ROLE),
+            pid: item
+                .proplist
+                .get_str(PROCESS_ID)
+                .and_then(|pid| pid.parse().ok()),
+            binary: item.proplist.get_str(PROCESS_BINARY),
             volume: item.volume.get()[0].0.clone(),
             mute: item.mute.clone(),
         }
     }
 }
 
+impl<'a> From<&'a introspect::SourceOutputInfo<'a>> for SourceOutputData {
+    fn from(item: &'a introspect::SourceOutputInfo<'a>) -> Self {
+        Self {
+            id: item.index,
+            name: item.proplist.get_str(APPLICATION_NAME).unwrap_or_default(),
+            role: item.proplist.get_str(MEDIA_ROLE),
+        }
+    }
+}
+
+impl<'a> From<&'a introspect::ServerInfo<'a>> for ServerData {
+    fn from(item: &'a introspect::ServerInfo<'a>) -> Self {
+        Self {
+            default_sink: item
+                .default_sink_name
+                .as_deref()
+                .unwrap_or_default()
+                .to_owned(),
+            default_source: item
+                .default_source_name
+                .as_deref()
+                .unwrap_or_default()
+                .to_owned(),
+            server_name: item.server_name.as_deref().unwrap_or_default().to_owned(),
+            server_version: item
+                .server_version
+                .as_deref()
+                .unwrap_or_default()
+                .to_owned(),
+            host_name: item.host_name.as_deref().unwrap_or_default().to_owned(),
+            user_name: item.user_name.as_deref().unwrap_or_default().to_owned(),
+            sample_spec: item.sample_spec.print(),
+            channel_map: item.channel_map.print(),
+        }
+    }
+}
+
+impl<'a> From<&'a introspect::SinkInfo<'a>> for DeviceData {
+    fn from(item: &'a introspect::SinkInfo<'a>) -> Self {
+        Self {
+            index: item.index,
+            name: item.name.as_deref().unwrap_or_default().to_owned(),
+            description: item.description.as_deref().unwrap_or_default().to_owned(),
+            volume: item.volume.get()[0].0,
+            mute: item.mute,
+            ports: item.ports.iter().map(PortData::from).collect(),
+            active_port: item
+                .active_port
+                .as_ref()
+                .and_then(|port| port.name.as_deref())
+                .map(String::from),
+        }
+    }
+}
+
+impl<'a> From<&'a introspect::SourceInfo<'a>> for DeviceData {
+    fn from(item: &'a introspect::SourceInfo<'a>) -> Self {
+        Self {
+            index: item.index,
+            name: item.name.as_deref().unwrap_or_default().to_owned(),
+            description: item.description.as_deref().unwrap_or_default().to_owned(),
+            volume: item.volume.get()[0].0,
+            mute: item.mute,
+            ports: item.ports.iter().map(PortData::from).collect(),
+            active_port: item
+                .active_port
+                .as_ref()
+                .and_then(|port| port.name.as_deref())
+                .map(String::from),
+        }
+    }
+}
+
+impl<'a> From<&'a introspect::SinkPortInfo<'a>> for PortData {
+    fn from(item: &'a introspect::SinkPortInfo<'a>) -> Self {
+        Self {
+            name: item.name.as_deref().unwrap_or_default().to_owned(),
+            description: item.description.as_deref().unwrap_or_default().to_owned(),
+            available: item.available.into(),
+        }
+    }
+}
+
+impl<'a> From<&'a introspect::SourcePortInfo<'a>> for PortData {
+    fn from(item: &'a introspect::SourcePortInfo<'a>) -> Self {
+        Self {
+            name: item.name.as_deref().unwrap_or_default().to_owned(),
+            description: item.description.as_deref().unwrap_or_default().to_owned(),
+            available: item.available.into(),
+        }
+    }
+}
+
+impl<'a> From<&'a introspect::ModuleInfo<'a>> for ModuleData {
+    fn from(item: &'a introspect::ModuleInfo<'a>) -> Self {
+        Self {
+            index: item.index,
+            name: item.name.as_deref().unwrap_or_default().to_owned(),
+            argument: item.argument.as_deref().unwrap_or_default().to_owned(),
+        }
+    }
+}
+
+impl<'a> From<&'a introspect::CardProfileInfo<'a>> for ProfileData {
+    fn from(item: &'a introspect::CardProfileInfo<'a>) -> Self {
+        Self {
+            name: item.name.as_deref().unwrap_or_default().to_owned(),
+            description: item.description.as_deref().unwrap_or_default().to_owned(),
+            available: item.available,
+        }
+    }
+}
+
+impl<'a> From<&'a introspect::CardInfo<'a>> for CardData {
+    fn from(item: &'a introspect::CardInfo<'a>) -> Self {
+        let name = item.name.as_deref().unwrap_or_default().to_owned();
+
+        Self {
+            index: item.index,
+            description: item
+                .proplist
+                .get_str(DEVICE_DESCRIPTION)
+                .unwrap_or_else(|| name.clone()),
+            name,
+            profiles: item.profiles.iter().map(ProfileData::from).collect(),
+            active_profile: item
+                .active_profile
+                .as_ref()
+                .and_then(|profile| profile.name.as_deref())
+                .map(String::from),
+        }
+    }
+}
+
 #[allow(dead_code)]
 impl<'a> From<&'a introspect::SinkInfo<'a>> for MainData {
     fn from(item: &'a introspect::SinkInfo<'a>) -> Self {
         Self {
+            name: item.name.as_deref().unwrap_or_default().to_owned(),
             volume: item.volume.get()[0].0.clone(),
             mute: item.mute.clone(),
         }
@@ -54,8 +300,115 @@ impl<'a> From<&'a introspect::SinkInfo<'a>> for MainData {
 impl<'a> From<&'a introspect::SourceInfo<'a>> for MainData {
     fn from(item: &'a introspect::SourceInfo<'a>) -> Self {
         Self {
+            name: item.name.as_deref().unwrap_or_default().to_owned(),
             volume: item.volume.get()[0].0.clone(),
             mute: item.mute.clone(),
         }
     }
 }
+
+impl<'a> From<&'a introspect::SinkInputInfo<'a>> for Details {
+    fn from(item: &'a introspect::SinkInputInfo<'a>) -> Self {
+        Self {
+            title: item
+                .proplist
+                .get_str(APPLICATION_NAME)
+                .unwrap_or_else(|| item.name.as_deref().unwrap_or_default().to_owned()),
+            fields: vec![
+                field("Index", item.index),
+                field("Sample spec", item.sample_spec.print()),
+                field("Channel map", item.channel_map.print()),
+                field("Buffer latency", latency(item.buffer_usec)),
+                field("Sink latency", latency(item.sink_usec)),
+                field(
+                    "Resample method",
+                    item.resample_method.as_deref().unwrap_or("none"),
+                ),
+                field("Driver", item.driver.as_deref().unwrap_or_default()),
+                field("Owner module", index(item.owner_module)),
+                field("Client", index(item.client)),
+                field("Sink", item.sink),
+            ],
+            properties: properties(&item.proplist),
+        }
+    }
+}
+
+impl<'a> From<&'a introspect::SinkInfo<'a>> for Details {
+    fn from(item: &'a introspect::SinkInfo<'a>) -> Self {
+        Self {
+            title: item.description.as_deref().unwrap_or_default().to_owned(),
+            fields: vec![
+                field("Index", item.index),
+                field("Name", item.name.as_deref().unwrap_or_default()),
+                field("Sample spec", item.sample_spec.print()),
+                field("Channel map", item.channel_map.print()),
+                field("Latency", latency(item.latency)),
+                field("Configured latency", latency(item.configured_latency)),
+                field("Driver", item.driver.as_deref().unwrap_or_default()),
+                field("Owner module", index(item.owner_module)),
+                field("Card", index(item.card)),
+            ],
+            properties: properties(&item.proplist),
+        }
+    }
+}
+
+impl<'a> From<&'a introspect::SourceInfo<'a>> for Details {
+    fn from(item: &'a introspect::SourceInfo<'a>) -> Self {
+        Self {
+            title: item.description.as_deref().unwrap_or_default().to_owned(),
+            fields: vec![
+                field("Index", item.index),
+                field("Name", item.name.as_deref().unwrap_or_default()),
+                field("Sample spec", item.sample_spec.print()),
+                field("Channel map", item.channel_map.print()),
+                field("Latency", latency(item.latency)),
+                field("Configured latency", latency(item.configured_latency)),
+                field("Driver", item.driver.as_deref().unwrap_or_default()),
+                field("Owner module", index(item.owner_module)),
+                field("Card", index(item.card)),
+            ],
+            properties: properties(&item.proplist),
+        }
+    }
+}
+
+impl Details {
+    /// The details as plain text, for the clipboard.
+    pub fn to_text(&self) -> String {
+        let mut text = format!("{}\n", self.title);
+        for (label, value) in &self.fields {
+            text.push_str(&format!("{}: {}\n", label, value));
+        }
+        text.push_str("\nProperties:\n");
+        for (key, value) in &self.properties {
+            text.push_str(&format!("    {} = \"{}\"\n", key, value));
+        }
+        text
+    }
+}
+
+fn field(label: &str, value: impl ToString) -> (String, String) {
+    (label.to_owned(), value.to_string())
+}
+
+fn index(index: Option<u32>) -> String {
+    index.map_or_else(|| String::from("none"), |index| index.to_string())
+}
+
+fn latency(latency: MicroSeconds) -> String {
+    format!("{:.1} ms", latency.0 as f64 / 1000.0)
+}
+
+fn properties(proplist: &Proplist) -> Vec<(String, String)> {
+    let mut properties: Vec<(String, String)> = proplist
+        .iter()
+        .map(|key| {
+            let value = proplist.get_str(&key).unwrap_or_default();
+            (key, value)
+        })
+        .collect();
+    properties.sort();
+    properties
+}
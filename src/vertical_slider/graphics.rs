@@ -0,0 +1,111 @@
+//! Draw a [`VerticalSlider`] with any `iced_graphics` backend.
+//!
+//! [`VerticalSlider`]: ../struct.VerticalSlider.html
+use iced_graphics::{Backend, Primitive, Renderer};
+use iced_native::mouse;
+use iced_native::{Background, Color, Point, Rectangle};
+use std::ops::RangeInclusive;
+
+pub use iced_style::slider::{HandleShape, StyleSheet};
+
+impl<B> super::Renderer for Renderer<B>
+where
+    B: Backend,
+{
+    type Style = Box<dyn StyleSheet>;
+
+    const DEFAULT_WIDTH: u16 = 22;
+
+    fn draw(
+        &mut self,
+        bounds: Rectangle,
+        cursor_position: Point,
+        range: RangeInclusive<f32>,
+        value: f32,
+        is_dragging: bool,
+        style_sheet: &Self::Style,
+    ) -> Self::Output {
+        let is_mouse_over = bounds.contains(cursor_position);
+
+        let style = if is_dragging {
+            style_sheet.dragging()
+        } else if is_mouse_over {
+            style_sheet.hovered()
+        } else {
+            style_sheet.active()
+        };
+
+        let rail_x = bounds.x + (bounds.width / 2.0).round();
+
+        let (rail_left, rail_right) = (
+            Primitive::Quad {
+                bounds: Rectangle {
+                    x: rail_x - 2.0,
+                    y: bounds.y,
+                    width: 2.0,
+                    height: bounds.height,
+                },
+                background: Background::Color(style.rail_colors.0),
+                border_radius: 0,
+                border_width: 0,
+                border_color: Color::TRANSPARENT,
+            },
+            Primitive::Quad {
+                bounds: Rectangle {
+                    x: rail_x,
+                    y: bounds.y,
+                    width: 2.0,
+                    height: bounds.height,
+                },
+                background: Background::Color(style.rail_colors.1),
+                border_radius: 0,
+                border_width: 0,
+                border_color: Color::TRANSPARENT,
+            },
+        );
+
+        let (handle_width, handle_height, handle_border_radius) = match style.handle.shape {
+            HandleShape::Circle { radius } => {
+                (f32::from(radius * 2), f32::from(radius * 2), radius)
+            }
+            HandleShape::Rectangle {
+                width,
+                border_radius,
+            } => (bounds.width, f32::from(width), border_radius),
+        };
+
+        let (range_start, range_end) = range.into_inner();
+
+        let handle_offset = if range_start >= range_end {
+            0.0
+        } else {
+            (bounds.height - handle_height) * (value - range_start) / (range_end - range_start)
+        };
+
+        let handle = Primitive::Quad {
+            bounds: Rectangle {
+                x: rail_x - handle_width / 2.0,
+                y: bounds.y + bounds.height - handle_height - handle_offset.round(),
+                width: handle_width,
+                height: handle_height,
+            },
+            background: Background::Color(style.handle.color),
+            border_radius: handle_border_radius,
+            border_width: style.handle.border_width,
+            border_color: style.handle.border_color,
+        };
+
+        (
+            Primitive::Group {
+                primitives: vec![rail_left, rail_right, handle],
+            },
+            if is_dragging {
+                mouse::Interaction::Grabbing
+            } else if is_mouse_over {
+                mouse::Interaction::Grab
+            } else {
+                mouse::Interaction::default()
+            },
+        )
+    }
+}
@@ -0,0 +1,244 @@
+//! Display an interactive vertical selector of a single value from a range
+//! of values.
+//!
+//! A [`VerticalSlider`] has some local [`State`].
+//!
+//! [`VerticalSlider`]: struct.VerticalSlider.html
+//! [`State`]: struct.State.html
+use iced_native::{
+    layout, mouse, Clipboard, Element, Event, Hasher, Layout, Length, Point, Rectangle, Size,
+    Widget,
+};
+use std::{hash::Hash, ops::RangeInclusive};
+
+mod graphics;
+mod null;
+
+/// A vertical bar and a handle that selects a single value from a range of
+/// values, with the start of the range at the bottom.
+///
+/// It is the vertical counterpart of the `iced` slider and shares its
+/// style sheet.
+#[allow(missing_debug_implementations)]
+pub struct VerticalSlider<'a, Message, Renderer: self::Renderer> {
+    state: &'a mut State,
+    range: RangeInclusive<f32>,
+    value: f32,
+    on_change: Box<dyn Fn(f32) -> Message>,
+    width: u16,
+    height: Length,
+    style: Renderer::Style,
+}
+
+impl<'a, Message, Renderer> VerticalSlider<'a, Message, Renderer>
+where
+    Renderer: self::Renderer,
+{
+    /// Creates a new [`VerticalSlider`].
+    ///
+    /// It expects:
+    ///   * the local [`State`] of the [`VerticalSlider`]
+    ///   * an inclusive range of possible values
+    ///   * the current value of the [`VerticalSlider`]
+    ///   * a function that will be called when the [`VerticalSlider`] is
+    ///     dragged.
+    ///
+    /// [`VerticalSlider`]: struct.VerticalSlider.html
+    /// [`State`]: struct.State.html
+    pub fn new<F>(state: &'a mut State, range: RangeInclusive<f32>, value: f32, on_change: F) -> Self
+    where
+        F: 'static + Fn(f32) -> Message,
+    {
+        VerticalSlider {
+            state,
+            value: value.max(*range.start()).min(*range.end()),
+            range,
+            on_change: Box::new(on_change),
+            width: Renderer::DEFAULT_WIDTH,
+            height: Length::Fill,
+            style: Renderer::Style::default(),
+        }
+    }
+
+    /// Sets the width of the [`VerticalSlider`].
+    ///
+    /// [`VerticalSlider`]: struct.VerticalSlider.html
+    pub fn width(mut self, width: u16) -> Self {
+        self.width = width;
+        self
+    }
+
+    /// Sets the height of the [`VerticalSlider`].
+    ///
+    /// [`VerticalSlider`]: struct.VerticalSlider.html
+    pub fn height(mut self, height: Length) -> Self {
+        self.height = height;
+        self
+    }
+
+    /// Sets the style of the [`VerticalSlider`].
+    ///
+    /// [`VerticalSlider`]: struct.VerticalSlider.html
+    pub fn style(mut self, style: impl Into<Renderer::Style>) -> Self {
+        self.style = style.into();
+        self
+    }
+}
+
+/// The local state of a [`VerticalSlider`].
+///
+/// [`VerticalSlider`]: struct.VerticalSlider.html
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct State {
+    is_dragging: bool,
+}
+
+impl State {
+    /// Creates a new [`State`].
+    ///
+    /// [`State`]: struct.State.html
+    pub fn new() -> State {
+        State::default()
+    }
+}
+
+impl<'a, Message, Renderer> Widget<Message, Renderer> for VerticalSlider<'a, Message, Renderer>
+where
+    Renderer: self::Renderer,
+{
+    fn width(&self) -> Length {
+        Length::Shrink
+    }
+
+    fn height(&self) -> Length {
+        self.height
+    }
+
+    fn layout(&self, _renderer: &Renderer, limits: &layout::Limits) -> layout::Node {
+        let limits = limits
+            .width(Length::Units(self.width))
+            .height(self.height);
+
+        let size = limits.resolve(Size::ZERO);
+
+        layout::Node::new(size)
+    }
+
+    fn on_event(
+        &mut self,
+        event: Event,
+        layout: Layout<'_>,
+        cursor_position: Point,
+        messages: &mut Vec<Message>,
+        _renderer: &Renderer,
+        _clipboard: Option<&dyn Clipboard>,
+    ) {
+        let mut change = || {
+            let bounds = layout.bounds();
+            let (start, end) = (*self.range.start(), *self.range.end());
+
+            let value = if cursor_position.y >= bounds.y + bounds.height {
+                start
+            } else if cursor_position.y <= bounds.y {
+                end
+            } else {
+                let percent = (bounds.y + bounds.height - cursor_position.y) / bounds.height;
+                start + percent * (end - start)
+            };
+
+            messages.push((self.on_change)(value));
+        };
+
+        match event {
+            Event::Mouse(mouse::Event::ButtonPressed(mouse::Button::Left)) => {
+                if layout.bounds().contains(cursor_position) {
+                    change();
+                    self.state.is_dragging = true;
+                }
+            }
+            Event::Mouse(mouse::Event::ButtonReleased(mouse::Button::Left)) => {
+                self.state.is_dragging = false;
+            }
+            Event::Mouse(mouse::Event::CursorMoved { .. }) => {
+                if self.state.is_dragging {
+                    change();
+                }
+            }
+            _ => {}
+        }
+    }
+
+    fn draw(
+        &self,
+        renderer: &mut Renderer,
+        _defaults: &Renderer::Defaults,
+        layout: Layout<'_>,
+        cursor_position: Point,
+    ) -> Renderer::Output {
+        renderer.draw(
+            layout.bounds(),
+            cursor_position,
+            self.range.clone(),
+            self.value,
+            self.state.is_dragging,
+            &self.style,
+        )
+    }
+
+    fn hash_layout(&self, state: &mut Hasher) {
+        struct Marker;
+        std::any::TypeId::of::<Marker>().hash(state);
+
+        self.width.hash(state);
+        self.height.hash(state);
+    }
+}
+
+/// The renderer of a [`VerticalSlider`].
+///
+/// Your [renderer] will need to implement this trait before being
+/// able to use a [`VerticalSlider`] in your user interface.
+///
+/// [`VerticalSlider`]: struct.VerticalSlider.html
+/// [renderer]: ../../renderer/index.html
+pub trait Renderer: iced_native::Renderer {
+    /// The style supported by this renderer.
+    type Style: Default;
+
+    /// The default width of a [`VerticalSlider`].
+    ///
+    /// [`VerticalSlider`]: struct.VerticalSlider.html
+    const DEFAULT_WIDTH: u16;
+
+    /// Draws a [`VerticalSlider`].
+    ///
+    /// It receives:
+    ///   * the bounds of the [`VerticalSlider`]
+    ///   * the current cursor position
+    ///   * the range of values of the [`VerticalSlider`]
+    ///   * the current value of the [`VerticalSlider`]
+    ///   * whether the [`VerticalSlider`] is being dragged
+    ///   * the style of the [`VerticalSlider`]
+    ///
+    /// [`VerticalSlider`]: struct.VerticalSlider.html
+    fn draw(
+        &mut self,
+        bounds: Rectangle,
+        cursor_position: Point,
+        range: RangeInclusive<f32>,
+        value: f32,
+        is_dragging: bool,
+        style: &Self::Style,
+    ) -> Self::Output;
+}
+
+impl<'a, Message, Renderer> From<VerticalSlider<'a, Message, Renderer>>
+    for Element<'a, Message, Renderer>
+where
+    Renderer: 'a + self::Renderer,
+    Message: 'a,
+{
+    fn from(slider: VerticalSlider<'a, Message, Renderer>) -> Element<'a, Message, Renderer> {
+        Element::new(slider)
+    }
+}
@@ -0,0 +1,20 @@
+use iced_native::renderer::Null;
+use iced_native::{Point, Rectangle};
+use std::ops::RangeInclusive;
+
+impl super::Renderer for Null {
+    type Style = ();
+
+    const DEFAULT_WIDTH: u16 = 22;
+
+    fn draw(
+        &mut self,
+        _bounds: Rectangle,
+        _cursor_position: Point,
+        _range: RangeInclusive<f32>,
+        _value: f32,
+        _is_dragging: bool,
+        _style: &(),
+    ) {
+    }
+}